@@ -1,2 +1,4 @@
 pub mod ray;
-pub mod vec3;
\ No newline at end of file
+pub mod random;
+pub mod vec3;
+pub mod spectrum;
@@ -1,17 +1,40 @@
+use crate::data_structs::random::random;
+use crate::data_structs::spectrum::rgb_to_spectrum;
 use crate::data_structs::vec3::{Color, Point3, Vec3};
-use crate::materials::{Emmitable, Scatterable};
+use crate::lights::{Background, LightList};
+use crate::materials::{Emmitable, Material, Scatterable};
 use crate::objects::hittables::{HitRecord, Hittable};
 
-#[derive(Default)]
+/// What a ray is traced for. Hittables can be hidden from some kinds of rays.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RayKind {
+    #[default]
+    Camera,
+    /// Visibility test towards a light.
+    Shadow,
+    /// Leaving a surface that scatters diffusely.
+    Diffuse,
+    /// Leaving a mirror, glass or other specular surface.
+    Specular,
+    /// Leaving a scattering event inside a participating medium.
+    Volume,
+}
+
+#[derive(Default, Copy, Clone)]
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
     pub time: f64,
+    /// Wavelength in nanometers once a wavelength dependent material has picked one for the path.
+    pub wavelength: Option<f64>,
+    pub kind: RayKind,
+    /// Light linking group of the surface the ray left. Camera rays are in the default group.
+    pub light_group: u32,
 }
 
 impl Ray {
     pub fn new(origin: Point3, direction: Vec3, time: f64) -> Self {
-        Ray { origin, direction, time }
+        Ray { origin, direction, time, wavelength: None, kind: RayKind::Camera, light_group: 0 }
     }
     pub fn at(&self, t: f64) -> Point3 {
         self.origin + t * self.direction
@@ -19,7 +42,79 @@ impl Ray {
 }
 
 
-pub fn ray_color(ray: &Ray, background: &Color, world: &dyn Hittable, depth: usize) -> Color {
+/// Sends part of the rays leaving diffuse surfaces towards the lights. Half of the rays follow the
+/// material's own sampling and the rest are split between the background and the registered emitters.
+/// Returns the factor that corrects the material attenuation for the mixture.
+fn sample_lights(ray: &Ray, record: &HitRecord, background: &Background, lights: &LightList, scattered: &mut Ray) -> f64 {
+    let sample_background = background.is_importance_sampled() && lights.background_links().illuminates(record.light_group);
+    let sample_emitters = lights.has_emitters(record.light_group);
+
+    if !(sample_background || sample_emitters) || record.material.scattering_pdf(ray, record, scattered) <= 0.0 {
+        return 1.0;
+    }
+
+    let light_share = 0.5 / (sample_background as u8 + sample_emitters as u8) as f64;
+    let choice = random::<f64>();
+    if choice >= 0.5 {
+        if sample_background && (!sample_emitters || choice < 0.5 + light_share) {
+            scattered.direction = background.sample_direction();
+        } else {
+            // A failed light sample still counts towards the mixture, it just brings in nothing.
+            match lights.sample_emitter_direction(&record.point, &record.normal, record.light_group) {
+                Some(direction) => scattered.direction = direction,
+                None => return 0.0,
+            }
+        }
+    }
+
+    let material_pdf = record.material.scattering_pdf(ray, record, scattered);
+    let mut mixture_pdf = 0.5 * material_pdf;
+    if sample_background {
+        mixture_pdf += light_share * background.pdf(&scattered.direction);
+    }
+    if sample_emitters {
+        mixture_pdf += light_share * lights.emitter_pdf(&record.point, &record.normal, &scattered.direction, record.light_group);
+    }
+
+    if mixture_pdf <= 0.0 { 0.0 } else { material_pdf / mixture_pdf }
+}
+
+/// Light reaching a diffuse surface straight from the delta lights, weighted by the material's
+/// cosine lobe. The material attenuation is applied by the caller. Lights that were picked
+/// among others are divided by the probability of picking them.
+fn sample_delta_lights(ray: &Ray, record: &HitRecord, lights: &LightList, world: &dyn Hittable) -> Color {
+    let mut direct = Color::ZERO;
+
+    for (light, probability) in lights.sample_delta_lights(&record.point, &record.normal, record.light_group) {
+        let mut direction = Vec3::ZERO;
+        let mut distance = 0.0;
+        let incident = light.illuminate(&record.point, &mut direction, &mut distance);
+
+        let shadow_ray = Ray { origin: record.point, direction, kind: RayKind::Shadow, ..*ray };
+        let cosine_weight = record.material.scattering_pdf(ray, record, &shadow_ray);
+        if cosine_weight <= 0.0 || incident.length_squared() <= 0.0 {
+            continue;
+        }
+
+        let mut shadow_record = HitRecord::default();
+        if !world.hit(&shadow_ray, 0.0001, distance - 0.0001, &mut shadow_record) {
+            direct += cosine_weight * incident / probability;
+        }
+    }
+
+    direct
+}
+
+/// Kind of the ray continuing the path from `record`.
+fn scattered_kind(ray: &Ray, record: &HitRecord, scattered: &Ray) -> RayKind {
+    match record.material {
+        Material::Isotropic(_) | Material::Subsurface(_) => RayKind::Volume,
+        _ if record.material.scattering_pdf(ray, record, scattered) > 0.0 => RayKind::Diffuse,
+        _ => RayKind::Specular,
+    }
+}
+
+pub fn ray_color(ray: &Ray, background: &Background, world: &dyn Hittable, lights: &LightList, depth: usize) -> Color {
     let mut record = HitRecord::default();
 
     // Exceeded bounce limit. End.
@@ -29,17 +124,74 @@ pub fn ray_color(ray: &Ray, background: &Color, world: &dyn Hittable, depth: usi
 
     // No hit -> Background color.
     if !world.hit(ray, 0.0001, f64::INFINITY, &mut record) {
-        return *background
+        if ray.kind != RayKind::Camera && !lights.background_links().illuminates(ray.light_group) {
+            return Color::ZERO
+        }
+        return background.ray_value(ray)
     }
 
     let mut scattered = Ray::default();
     let mut attenuation = Color::ZERO;
-    let emitted = record.material.emitted(record.u, record.v, &record.point);
+    let emitted = if record.light_unlinked { Color::ZERO } else { record.material.emitted(ray, &record) };
 
     // If material is not scattering, return emitted color.
     if !record.material.scatter(ray, &record, &mut attenuation, &mut scattered) {
         return emitted
     }
 
-    emitted + attenuation * ray_color(&scattered, background, world, depth - 1)
-}
\ No newline at end of file
+    let direct = sample_delta_lights(ray, &record, lights, world);
+    let light_weight = sample_lights(ray, &record, background, lights, &mut scattered);
+    if light_weight <= 0.0 {
+        return emitted + attenuation * direct
+    }
+
+    // Scattered rays stay on the wavelength picked earlier in the path.
+    if scattered.wavelength.is_none() {
+        scattered.wavelength = ray.wavelength;
+    }
+    scattered.kind = scattered_kind(ray, &record, &scattered);
+    scattered.light_group = record.light_group;
+
+    emitted + attenuation * (direct + light_weight * ray_color(&scattered, background, world, lights, depth - 1))
+}
+
+/// Spectral counterpart of `ray_color`. Returns the radiance at the wavelength carried by the ray.
+pub fn spectral_ray_color(ray: &Ray, background: &Background, world: &dyn Hittable, lights: &LightList, depth: usize) -> f64 {
+    let wavelength = ray.wavelength.expect("Spectral paths need a wavelength.");
+    let mut record = HitRecord::default();
+
+    // Exceeded bounce limit. End.
+    if depth == 0 {
+        return 0.0;
+    }
+
+    // No hit -> Background color.
+    if !world.hit(ray, 0.0001, f64::INFINITY, &mut record) {
+        if ray.kind != RayKind::Camera && !lights.background_links().illuminates(ray.light_group) {
+            return 0.0;
+        }
+        return background.ray_value_spectral(ray, wavelength);
+    }
+
+    let mut scattered = Ray::default();
+    let mut attenuation = Color::ZERO;
+    let emitted = if record.light_unlinked { 0.0 } else { record.material.emitted_spectral(ray, &record, wavelength) };
+
+    // If material is not scattering, return emitted radiance.
+    if !record.material.scatter(ray, &record, &mut attenuation, &mut scattered) {
+        return emitted
+    }
+
+    let direct = rgb_to_spectrum(sample_delta_lights(ray, &record, lights, world), wavelength);
+    let light_weight = sample_lights(ray, &record, background, lights, &mut scattered);
+    if light_weight <= 0.0 {
+        return emitted + rgb_to_spectrum(attenuation, wavelength) * direct
+    }
+
+    scattered.wavelength = Some(wavelength);
+    scattered.kind = scattered_kind(ray, &record, &scattered);
+    scattered.light_group = record.light_group;
+
+    emitted + rgb_to_spectrum(attenuation, wavelength)
+        * (direct + light_weight * spectral_ray_color(&scattered, background, world, lights, depth - 1))
+}
@@ -0,0 +1,174 @@
+use std::sync::OnceLock;
+
+use crate::data_structs::random::random;
+use crate::data_structs::vec3::{Color, Vec3};
+
+/// Visible range sampled by wavelength dependent materials, in nanometers.
+pub const MIN_WAVELENGTH: f64 = 380.0;
+pub const MAX_WAVELENGTH: f64 = 780.0;
+
+const NORMALIZATION_STEPS: usize = 400;
+
+/// Uniformly samples a wavelength in the visible range.
+pub fn sample_wavelength() -> f64 {
+    MIN_WAVELENGTH + random::<f64>() * (MAX_WAVELENGTH - MIN_WAVELENGTH)
+}
+
+fn piecewise_gaussian(wavelength: f64, mean: f64, sigma_low: f64, sigma_high: f64) -> f64 {
+    let sigma = if wavelength < mean { sigma_low } else { sigma_high };
+    let t = (wavelength - mean) / sigma;
+
+    (-0.5 * t * t).exp()
+}
+
+/// CIE 1931 color matching functions, using the multi-lobe fit by Wyman, Sloan and Shirley.
+pub fn wavelength_to_xyz(wavelength: f64) -> Vec3 {
+    let x = 1.056 * piecewise_gaussian(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(wavelength, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(wavelength, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(wavelength, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(wavelength, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(wavelength, 459.0, 26.0, 13.8);
+
+    Vec3::new(x, y, z)
+}
+
+/// Converts CIE XYZ to linear sRGB.
+pub fn xyz_to_rgb(xyz: Vec3) -> Color {
+    Color::new(
+        3.2404542 * xyz.x - 1.5371385 * xyz.y - 0.4985314 * xyz.z,
+        -0.9692660 * xyz.x + 1.8760108 * xyz.y + 0.0415560 * xyz.z,
+        0.0556434 * xyz.x - 0.2040259 * xyz.y + 1.0572252 * xyz.z,
+    )
+}
+
+fn clamped_rgb(wavelength: f64) -> Color {
+    let rgb = xyz_to_rgb(wavelength_to_xyz(wavelength));
+
+    Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+}
+
+/// RGB throughput of a path that carries a single wavelength.
+/// Normalized so that averaging over uniformly sampled wavelengths gives white.
+pub fn wavelength_to_rgb_weight(wavelength: f64) -> Color {
+    static AVERAGE_RGB: OnceLock<Color> = OnceLock::new();
+
+    let average = AVERAGE_RGB.get_or_init(|| {
+        let step = (MAX_WAVELENGTH - MIN_WAVELENGTH) / NORMALIZATION_STEPS as f64;
+
+        (0..NORMALIZATION_STEPS)
+            .map(|i| clamped_rgb(MIN_WAVELENGTH + (i as f64 + 0.5) * step))
+            .sum::<Color>() / NORMALIZATION_STEPS as f64
+    });
+
+    clamped_rgb(wavelength) / *average
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RenderMode {
+    /// Paths carry RGB throughput. Only wavelength dependent materials pick a wavelength.
+    Rgb,
+    /// Every camera path samples a wavelength, and colors are upsampled to spectra along the path.
+    Spectral,
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Upsamples an RGB color to a smooth spectrum and evaluates it at `wavelength`.
+/// The three bands sum to one, so white stays flat and reflectances stay below one.
+pub fn rgb_to_spectrum(color: Color, wavelength: f64) -> f64 {
+    let blue_to_green = sigmoid((wavelength - 490.0) / 10.0);
+    let green_to_red = sigmoid((wavelength - 580.0) / 10.0);
+
+    color.x * green_to_red
+        + color.y * (blue_to_green - green_to_red)
+        + color.z * (1.0 - blue_to_green)
+}
+
+/// Emission spectrum of a light source.
+#[derive(Clone)]
+pub enum Spectrum {
+    /// Planck's law at a temperature in kelvin, normalized to the luminance of a flat white spectrum.
+    Blackbody { temperature: f64, normalization: f64 },
+    /// Measured values at increasing wavelengths in nanometers, linearly interpolated.
+    Tabulated { wavelengths: Vec<f64>, values: Vec<f64> },
+}
+
+impl Spectrum {
+    /// Black body emitter with the luminance of a flat spectrum with value `scale`.
+    pub fn blackbody(temperature: f64, scale: f64) -> Self {
+        Spectrum::Blackbody { temperature, normalization: scale / planck_luminance(temperature) }
+    }
+
+    pub fn tabulated(wavelengths: Vec<f64>, values: Vec<f64>) -> Self {
+        assert_eq!(wavelengths.len(), values.len(), "Tabulated spectrum needs one value per wavelength.");
+        assert!(!wavelengths.is_empty(), "Tabulated spectrum needs at least one sample.");
+
+        Spectrum::Tabulated { wavelengths, values }
+    }
+
+    pub fn value(&self, wavelength: f64) -> f64 {
+        match self {
+            Spectrum::Blackbody { temperature, normalization } => {
+                normalization * planck(wavelength, *temperature)
+            }
+            Spectrum::Tabulated { wavelengths, values } => {
+                let last = wavelengths.len() - 1;
+                if wavelength <= wavelengths[0] {
+                    return values[0];
+                }
+                if wavelength >= wavelengths[last] {
+                    return values[last];
+                }
+
+                let upper = wavelengths.partition_point(|&w| w < wavelength);
+                let lower = upper - 1;
+                let t = (wavelength - wavelengths[lower]) / (wavelengths[upper] - wavelengths[lower]);
+
+                (1.0 - t) * values[lower] + t * values[upper]
+            }
+        }
+    }
+
+    /// Linear RGB matching what the spectral film records for this spectrum.
+    pub fn to_rgb(&self) -> Color {
+        let step = (MAX_WAVELENGTH - MIN_WAVELENGTH) / NORMALIZATION_STEPS as f64;
+
+        (0..NORMALIZATION_STEPS)
+            .map(|i| {
+                let wavelength = MIN_WAVELENGTH + (i as f64 + 0.5) * step;
+                self.value(wavelength) * wavelength_to_rgb_weight(wavelength)
+            })
+            .sum::<Color>() / NORMALIZATION_STEPS as f64
+    }
+}
+
+/// Spectral radiance of a black body, with the wavelength in nanometers.
+fn planck(wavelength: f64, temperature: f64) -> f64 {
+    const PLANCK: f64 = 6.62606957e-34;
+    const BOLTZMANN: f64 = 1.3806488e-23;
+    const SPEED_OF_LIGHT: f64 = 299792458.0;
+
+    let meters = wavelength * 1e-9;
+
+    2.0 * PLANCK * SPEED_OF_LIGHT * SPEED_OF_LIGHT
+        / (meters.powi(5) * ((PLANCK * SPEED_OF_LIGHT / (meters * BOLTZMANN * temperature)).exp() - 1.0))
+}
+
+/// Luminance of the Planck spectrum relative to a flat spectrum with value one.
+fn planck_luminance(temperature: f64) -> f64 {
+    let step = (MAX_WAVELENGTH - MIN_WAVELENGTH) / NORMALIZATION_STEPS as f64;
+    let wavelengths = (0..NORMALIZATION_STEPS).map(|i| MIN_WAVELENGTH + (i as f64 + 0.5) * step);
+
+    let weighted = wavelengths.clone()
+        .map(|wavelength| planck(wavelength, temperature) * wavelength_to_xyz(wavelength).y)
+        .sum::<f64>();
+    let flat = wavelengths
+        .map(|wavelength| wavelength_to_xyz(wavelength).y)
+        .sum::<f64>();
+
+    weighted / flat
+}
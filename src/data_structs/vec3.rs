@@ -2,7 +2,8 @@ use std::iter::Sum;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use image::Rgb;
-use rand::{Rng, thread_rng};
+use rand::Rng;
+use crate::data_structs::random::{random, rng};
 
 pub use Vec3 as Color;
 pub use Vec3 as Point3;
@@ -77,6 +78,11 @@ impl Vec3 {
         }
     }
 
+    /// Relative luminance of a linear RGB color.
+    pub fn luminance(self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
+
     pub fn write_color(&self, samples_per_pixel: usize) -> Rgb<u8> {
         let mut r = self.x;
         let mut g = self.y;
@@ -96,14 +102,14 @@ impl Vec3 {
 
     pub fn random() -> Vec3 {
         Vec3 {
-            x: rand::random::<f64>(),
-            y: rand::random::<f64>(),
-            z: rand::random::<f64>(),
+            x: random::<f64>(),
+            y: random::<f64>(),
+            z: random::<f64>(),
         }
     }
 
     pub fn random_with_limits(min: f64, max: f64) -> Vec3 {
-        let mut rng = thread_rng();
+        let mut rng = rng();
 
         Vec3 {
             x: rng.gen_range(min..max),
@@ -113,7 +119,7 @@ impl Vec3 {
     }
 
     pub fn random_in_unit_disk() -> Point3 {
-        let mut rng = thread_rng();
+        let mut rng = rng();
 
         loop {
             let point = Vec3::new(
@@ -2,3 +2,5 @@ pub mod materials;
 pub mod objects;
 pub mod data_structs;
 pub mod scenes;
+pub mod lights;
+pub mod render;
@@ -0,0 +1,137 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+use crate::data_structs::vec3::{Color, Point3, Vec3};
+use crate::lights::ies::IesProfile;
+
+/// Lights without any geometry. They can't be hit by rays, so they are only reached by sampling them
+/// explicitly from the surfaces they light.
+#[derive(Clone)]
+pub enum DeltaLight {
+    Point {
+        position: Point3,
+        intensity: Color,
+        profile: Option<Arc<IesProfile>>,
+    },
+    Spot {
+        position: Point3,
+        direction: Vec3,
+        intensity: Color,
+        cos_inner: f64,
+        cos_outer: f64,
+        profile: Option<Arc<IesProfile>>,
+    },
+    Directional {
+        direction: Vec3,
+        irradiance: Color,
+    },
+}
+
+impl DeltaLight {
+    /// Light radiating `intensity` (radiant intensity) equally in every direction.
+    pub fn point(position: Point3, intensity: Color) -> Self {
+        DeltaLight::Point { position, intensity, profile: None }
+    }
+
+    /// Light pointing at `target`. It is at full intensity inside `inner_angle` and fades out smoothly
+    /// until `outer_angle`. Angles are measured from the axis, in degrees.
+    pub fn spot(position: Point3, target: Point3, intensity: Color, inner_angle: f64, outer_angle: f64) -> Self {
+        let outer_angle = outer_angle.max(inner_angle);
+
+        DeltaLight::Spot {
+            position,
+            direction: (target - position).normalize(),
+            intensity,
+            cos_inner: inner_angle.to_radians().cos(),
+            cos_outer: outer_angle.to_radians().cos(),
+            profile: None,
+        }
+    }
+
+    /// Light coming from infinitely far away, travelling along `direction`.
+    pub fn directional(direction: Vec3, irradiance: Color) -> Self {
+        DeltaLight::Directional { direction: direction.normalize(), irradiance }
+    }
+
+    /// Shapes a point or spot light with a measured fixture distribution. `intensity` then scales the
+    /// candela values of the profile. Point lights aim the fixture straight down.
+    pub fn with_profile(self, profile: IesProfile) -> Self {
+        match self {
+            DeltaLight::Point { position, intensity, .. } => {
+                DeltaLight::Point { position, intensity, profile: Some(Arc::new(profile)) }
+            }
+            DeltaLight::Spot { position, direction, intensity, cos_inner, cos_outer, .. } => {
+                DeltaLight::Spot { position, direction, intensity, cos_inner, cos_outer, profile: Some(Arc::new(profile)) }
+            }
+            directional => directional,
+        }
+    }
+
+    /// Position of point and spot lights. Directional lights are infinitely far away.
+    pub fn position(&self) -> Option<Point3> {
+        match self {
+            DeltaLight::Point { position, .. } | DeltaLight::Spot { position, .. } => Some(*position),
+            DeltaLight::Directional { .. } => None,
+        }
+    }
+
+    /// Rough total power of a point or spot light, used to pick between lights.
+    pub fn power(&self) -> f64 {
+        let profile_scale = |profile: &Option<Arc<IesProfile>>| profile.as_ref().map_or(1.0, |profile| profile.max_candela());
+
+        match self {
+            DeltaLight::Point { intensity, profile, .. } => {
+                intensity.luminance() * profile_scale(profile) * 4.0 * PI
+            }
+            DeltaLight::Spot { intensity, cos_inner, cos_outer, profile, .. } => {
+                intensity.luminance() * profile_scale(profile) * 2.0 * PI * (1.0 - (cos_inner + cos_outer) / 2.0)
+            }
+            DeltaLight::Directional { irradiance, .. } => irradiance.luminance(),
+        }
+    }
+
+    /// Returns the light arriving at `point`. Sets the unit direction towards the light and the distance
+    /// a shadow ray has to travel to reach it.
+    pub fn illuminate(&self, point: &Point3, direction: &mut Vec3, distance: &mut f64) -> Color {
+        match self {
+            DeltaLight::Point { position, intensity, profile } => {
+                let to_light = *position - *point;
+                *distance = to_light.length();
+                *direction = to_light / *distance;
+
+                let down = Vec3::new(0.0, -1.0, 0.0);
+                let shape = Self::profile_scale(profile, &-*direction, &down);
+
+                shape * *intensity / (*distance * *distance)
+            }
+            DeltaLight::Spot { position, direction: axis, intensity, cos_inner, cos_outer, profile } => {
+                let to_light = *position - *point;
+                *distance = to_light.length();
+                *direction = to_light / *distance;
+
+                let cos_theta = -direction.dot(*axis);
+                let falloff = if cos_theta >= *cos_inner {
+                    1.0
+                } else if cos_theta <= *cos_outer {
+                    0.0
+                } else {
+                    let t = (cos_theta - cos_outer) / (cos_inner - cos_outer);
+                    t * t * (3.0 - 2.0 * t)
+                };
+
+                let shape = Self::profile_scale(profile, &-*direction, axis);
+
+                falloff * shape * *intensity / (*distance * *distance)
+            }
+            DeltaLight::Directional { direction: light_direction, irradiance } => {
+                *direction = -*light_direction;
+                *distance = f64::INFINITY;
+
+                *irradiance
+            }
+        }
+    }
+
+    fn profile_scale(profile: &Option<Arc<IesProfile>>, direction: &Vec3, axis: &Vec3) -> f64 {
+        profile.as_ref().map_or(1.0, |profile| profile.candela_about(direction, axis))
+    }
+}
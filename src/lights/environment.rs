@@ -0,0 +1,143 @@
+use std::f64::consts::PI;
+use image::io::Reader as ImageReader;
+use crate::data_structs::random::random;
+use crate::data_structs::vec3::{Color, Vec3};
+use crate::objects::hittables::sphere::Sphere;
+
+/// Equirectangular environment image lighting the scene from infinitely far away.
+/// Directions are importance sampled through a 2D CDF over the luminance of the pixels.
+pub struct EnvironmentMap {
+    pixels: Vec<Color>,
+    width: usize,
+    height: usize,
+    intensity: f64,
+    sin_rotation: f64,
+    cos_rotation: f64,
+    /// Cumulative weight of the rows, normalized to end at one.
+    marginal_cdf: Vec<f64>,
+    /// Cumulative weight of the pixels in each row, `width + 1` entries per row.
+    conditional_cdfs: Vec<f64>,
+    /// Probability of picking each pixel.
+    pixel_probabilities: Vec<f64>,
+}
+
+impl EnvironmentMap {
+    /// Loads an `.hdr`, `.exr` or any other image supported by `image`.
+    /// `rotation` turns the map around the y axis, in degrees.
+    pub fn new(filename: &str, intensity: f64, rotation: f64) -> Self {
+        let img = ImageReader::open(filename)
+            .expect("Environment map file not found.")
+            .decode()
+            .unwrap()
+            .to_rgb32f();
+
+        let width = img.width() as usize;
+        let height = img.height() as usize;
+        let pixels = img.pixels()
+            .map(|pixel| Color::new(pixel[0] as f64, pixel[1] as f64, pixel[2] as f64))
+            .collect::<Vec<Color>>();
+
+        // Rows near the poles cover less solid angle.
+        let weights = (0..height)
+            .flat_map(|j| {
+                let sin_theta = (PI * (j as f64 + 0.5) / height as f64).sin();
+                pixels[j * width..(j + 1) * width].iter().map(move |pixel| pixel.luminance().max(0.0) * sin_theta)
+            })
+            .collect::<Vec<f64>>();
+        let total = weights.iter().sum::<f64>().max(f64::MIN_POSITIVE);
+
+        let mut marginal_cdf = vec![0.0; height + 1];
+        let mut conditional_cdfs = vec![0.0; height * (width + 1)];
+
+        for j in 0..height {
+            let row = &weights[j * width..(j + 1) * width];
+            let row_total = row.iter().sum::<f64>();
+            let cdf = &mut conditional_cdfs[j * (width + 1)..(j + 1) * (width + 1)];
+
+            for i in 0..width {
+                cdf[i + 1] = if row_total > 0.0 {
+                    cdf[i] + row[i] / row_total
+                } else {
+                    (i + 1) as f64 / width as f64
+                };
+            }
+
+            marginal_cdf[j + 1] = marginal_cdf[j] + row_total / total;
+        }
+
+        let pixel_probabilities = weights.iter().map(|weight| weight / total).collect();
+        let radians = rotation * PI / 180.0;
+
+        Self {
+            pixels,
+            width,
+            height,
+            intensity,
+            sin_rotation: radians.sin(),
+            cos_rotation: radians.cos(),
+            marginal_cdf,
+            conditional_cdfs,
+            pixel_probabilities,
+        }
+    }
+
+    fn rotate(&self, direction: &Vec3, sin_rotation: f64) -> Vec3 {
+        Vec3::new(
+            self.cos_rotation * direction.x + sin_rotation * direction.z,
+            direction.y,
+            -sin_rotation * direction.x + self.cos_rotation * direction.z,
+        )
+    }
+
+    /// Pixel column and row that `direction` looks up, plus the sine of its polar angle.
+    fn pixel_coordinates(&self, direction: &Vec3) -> (usize, usize, f64) {
+        let local = self.rotate(&direction.normalize(), -self.sin_rotation);
+
+        let (mut u, mut v) = (0.0, 0.0);
+        Sphere::get_sphere_uv(&local, &mut u, &mut v);
+
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = (((1.0 - v) * self.height as f64) as usize).min(self.height - 1);
+
+        (i, j, (PI * v).sin())
+    }
+
+    pub fn value(&self, direction: &Vec3) -> Color {
+        let (i, j, _) = self.pixel_coordinates(direction);
+
+        self.intensity * self.pixels[j * self.width + i]
+    }
+
+    pub fn sample_direction(&self) -> Vec3 {
+        let j = sample_cdf(&self.marginal_cdf, random::<f64>());
+        let i = sample_cdf(&self.conditional_cdfs[j * (self.width + 1)..(j + 1) * (self.width + 1)], random::<f64>());
+
+        let u = (i as f64 + random::<f64>()) / self.width as f64;
+        let v = 1.0 - (j as f64 + random::<f64>()) / self.height as f64;
+
+        // Inverse of `Sphere::get_sphere_uv`.
+        let phi = 2.0 * PI * u;
+        let theta = PI * v;
+        let local = Vec3::new(-phi.cos() * theta.sin(), -theta.cos(), phi.sin() * theta.sin());
+
+        self.rotate(&local, self.sin_rotation)
+    }
+
+    pub fn pdf(&self, direction: &Vec3) -> f64 {
+        let (i, j, sin_theta) = self.pixel_coordinates(direction);
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+
+        let pixel_solid_angle = 2.0 * PI * PI * sin_theta / (self.width * self.height) as f64;
+
+        self.pixel_probabilities[j * self.width + i] / pixel_solid_angle
+    }
+}
+
+/// Index of the interval of a CDF that contains `sample`.
+fn sample_cdf(cdf: &[f64], sample: f64) -> usize {
+    let scaled = sample * cdf[cdf.len() - 1];
+
+    cdf.partition_point(|&value| value <= scaled).clamp(1, cdf.len() - 1) - 1
+}
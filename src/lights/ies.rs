@@ -0,0 +1,151 @@
+use std::fs;
+use crate::data_structs::vec3::Vec3;
+
+/// Angular intensity distribution of a light fixture, read from an IES LM-63 photometric file.
+/// Only type C photometry is supported, the type used by nearly all architectural fixtures.
+pub struct IesProfile {
+    /// Angles from the fixture's nadir, in degrees.
+    vertical_angles: Vec<f64>,
+    /// Angles around the fixture's axis, in degrees.
+    horizontal_angles: Vec<f64>,
+    /// Candela values, `vertical_angles.len()` entries per horizontal angle.
+    candela: Vec<f64>,
+    max_candela: f64,
+}
+
+impl IesProfile {
+    pub fn new(filename: &str) -> Self {
+        let contents = fs::read_to_string(filename).expect("IES file not found.");
+
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        // Keyword lines come first and end with the TILT line. The rest is a flat list of numbers.
+        let mut lines = contents.lines();
+        let tilt = lines
+            .find(|line| line.trim_start().starts_with("TILT="))
+            .expect("IES file has no TILT line.");
+
+        let mut numbers = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ',').collect::<Vec<&str>>())
+            .filter(|token| !token.is_empty())
+            .map(|token| token.parse::<f64>().expect("IES file has an invalid number."));
+        let mut next = || numbers.next().expect("IES file ends early.");
+
+        if tilt.trim() == "TILT=INCLUDE" {
+            let _lamp_to_luminaire_geometry = next();
+            let pairs = next() as usize;
+            for _ in 0..2 * pairs {
+                next();
+            }
+        }
+
+        let _lamps = next();
+        let _lumens_per_lamp = next();
+        let multiplier = next();
+        let vertical_count = next() as usize;
+        let horizontal_count = next() as usize;
+        let photometric_type = next() as usize;
+        let _units = next();
+        let _width = next();
+        let _length = next();
+        let _height = next();
+        let ballast_factor = next();
+        let _ballast_lamp_factor = next();
+        let _input_watts = next();
+
+        assert_eq!(photometric_type, 1, "Only type C IES photometry is supported.");
+
+        let vertical_angles = (0..vertical_count).map(|_| next()).collect::<Vec<f64>>();
+        let horizontal_angles = (0..horizontal_count).map(|_| next()).collect::<Vec<f64>>();
+        let candela = (0..vertical_count * horizontal_count)
+            .map(|_| next() * multiplier * ballast_factor)
+            .collect::<Vec<f64>>();
+        let max_candela = candela.iter().cloned().fold(0.0, f64::max);
+
+        Self { vertical_angles, horizontal_angles, candela, max_candela }
+    }
+
+    /// Intensity in candela at a vertical angle from the nadir and a horizontal angle around it, in degrees.
+    pub fn candela_at(&self, vertical: f64, horizontal: f64) -> f64 {
+        let first_vertical = self.vertical_angles[0];
+        let last_vertical = self.vertical_angles[self.vertical_angles.len() - 1];
+        if vertical < first_vertical || vertical > last_vertical {
+            return 0.0;
+        }
+
+        // Files only list the part of the distribution that isn't repeated by symmetry.
+        let mut horizontal = horizontal.rem_euclid(360.0);
+        let last_horizontal = self.horizontal_angles[self.horizontal_angles.len() - 1];
+        if last_horizontal <= 90.0 && horizontal > 180.0 {
+            horizontal = 360.0 - horizontal;
+        }
+        if last_horizontal <= 90.0 && horizontal > 90.0 {
+            horizontal = 180.0 - horizontal;
+        }
+        if last_horizontal <= 180.0 && horizontal > 180.0 {
+            horizontal = 360.0 - horizontal;
+        }
+
+        let (v0, v1, tv) = Self::bracket(&self.vertical_angles, vertical);
+        let (h0, h1, th) = Self::bracket(&self.horizontal_angles, horizontal);
+
+        let count = self.vertical_angles.len();
+        let value = |h: usize, v: usize| self.candela[h * count + v];
+        let lower = value(h0, v0) * (1.0 - tv) + value(h0, v1) * tv;
+        let upper = value(h1, v0) * (1.0 - tv) + value(h1, v1) * tv;
+
+        lower * (1.0 - th) + upper * th
+    }
+
+    /// Intensity in candela along `direction`, for a fixture pointing along `axis`.
+    /// Horizontal angles start from `reference`, a direction perpendicular to the axis.
+    pub fn candela(&self, direction: &Vec3, axis: &Vec3, reference: &Vec3) -> f64 {
+        let direction = direction.normalize();
+        let vertical = direction.dot(*axis).clamp(-1.0, 1.0).acos().to_degrees();
+        let side = axis.cross(reference);
+        let horizontal = direction.dot(side).atan2(direction.dot(*reference)).to_degrees();
+
+        self.candela_at(vertical, horizontal)
+    }
+
+    /// Intensity of the brightest direction, in candela.
+    pub fn max_candela(&self) -> f64 {
+        self.max_candela
+    }
+
+    /// Intensity in candela along `direction` for a fixture aimed along `axis`, with the horizontal angles
+    /// starting from an arbitrary but fixed direction perpendicular to the axis.
+    pub fn candela_about(&self, direction: &Vec3, axis: &Vec3) -> f64 {
+        let helper = if axis.x.abs() > 0.9 { Vec3::new(0.0, 0.0, 1.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let reference = axis.cross(&helper).cross(axis).normalize();
+
+        self.candela(direction, axis, &reference)
+    }
+
+    /// Candela per unit of area seen from `direction`, for a surface emitting the profile about its
+    /// outward `normal`. This is the radiance of an emitter with a candela per unit area of one.
+    pub fn projected_candela(&self, direction: &Vec3, normal: &Vec3) -> f64 {
+        let cos_theta = direction.normalize().dot(*normal);
+        if cos_theta <= 0.0 {
+            return 0.0;
+        }
+
+        self.candela_about(direction, normal) / cos_theta
+    }
+
+    /// Returns the indices around `angle` in a sorted list and the interpolation weight between them.
+    fn bracket(angles: &[f64], angle: f64) -> (usize, usize, f64) {
+        if angles.len() == 1 {
+            return (0, 0, 0.0);
+        }
+
+        let upper = angles.partition_point(|a| *a < angle).clamp(1, angles.len() - 1);
+        let lower = upper - 1;
+        let span = angles[upper] - angles[lower];
+        let t = if span > 0.0 { ((angle - angles[lower]) / span).clamp(0.0, 1.0) } else { 0.0 };
+
+        (lower, upper, t)
+    }
+}
@@ -0,0 +1,217 @@
+use crate::data_structs::random::random;
+use crate::data_structs::vec3::{Point3, Vec3};
+
+/// Position, extent and power of one light in a `LightTree`.
+pub struct LightBounds {
+    index: usize,
+    center: Point3,
+    radius: f64,
+    power: f64,
+}
+
+impl LightBounds {
+    /// `index` is what the tree hands back when the light is picked.
+    pub fn new(index: usize, center: Point3, radius: f64, power: f64) -> Self {
+        Self { index, center, radius, power }
+    }
+}
+
+#[derive(Clone)]
+enum LightNodeKind {
+    Leaf(usize),
+    Interior(usize, usize),
+}
+
+/// Bounding sphere and total power of a group of lights.
+#[derive(Clone)]
+struct LightNode {
+    center: Point3,
+    radius: f64,
+    power: f64,
+    kind: LightNodeKind,
+}
+
+/// Bounding volume hierarchy over lights. Walking down from the root, each child is picked by its
+/// power over its squared distance to the shading point, so bright and nearby lights are picked
+/// most often. Lights below the shaded surface are never picked. Picking a light and finding the probability of a direction both take logarithmic time.
+#[derive(Clone, Default)]
+pub struct LightTree {
+    nodes: Vec<LightNode>,
+}
+
+impl LightTree {
+    pub fn new(mut lights: Vec<LightBounds>) -> Self {
+        lights.retain(|light| light.power > 0.0);
+
+        let mut tree = Self { nodes: Vec::with_capacity(2 * lights.len()) };
+        if !lights.is_empty() {
+            tree.build(&mut lights);
+        }
+
+        tree
+    }
+
+    /// Adds the subtree over `lights` and returns the index of its root.
+    fn build(&mut self, lights: &mut [LightBounds]) -> usize {
+        if lights.len() == 1 {
+            let light = &lights[0];
+            self.nodes.push(LightNode {
+                center: light.center,
+                radius: light.radius,
+                power: light.power,
+                kind: LightNodeKind::Leaf(light.index),
+            });
+
+            return self.nodes.len() - 1;
+        }
+
+        // Split at the median along the longest extent of the light centers.
+        let mut minimum = lights[0].center;
+        let mut maximum = lights[0].center;
+        for light in lights.iter() {
+            minimum = Point3::new(minimum.x.min(light.center.x), minimum.y.min(light.center.y), minimum.z.min(light.center.z));
+            maximum = Point3::new(maximum.x.max(light.center.x), maximum.y.max(light.center.y), maximum.z.max(light.center.z));
+        }
+        let extent = maximum - minimum;
+        let axis = |point: &Point3| {
+            if extent.x >= extent.y && extent.x >= extent.z {
+                point.x
+            } else if extent.y >= extent.z {
+                point.y
+            } else {
+                point.z
+            }
+        };
+        lights.sort_by(|a, b| axis(&a.center).total_cmp(&axis(&b.center)));
+
+        let (left_lights, right_lights) = lights.split_at_mut(lights.len() / 2);
+        let left = self.build(left_lights);
+        let right = self.build(right_lights);
+
+        let (center, radius) = Self::enclosing_sphere(&self.nodes[left], &self.nodes[right]);
+        self.nodes.push(LightNode {
+            center,
+            radius,
+            power: self.nodes[left].power + self.nodes[right].power,
+            kind: LightNodeKind::Interior(left, right),
+        });
+
+        self.nodes.len() - 1
+    }
+
+    fn enclosing_sphere(a: &LightNode, b: &LightNode) -> (Point3, f64) {
+        let offset = b.center - a.center;
+        let distance = offset.length();
+
+        if distance + b.radius <= a.radius {
+            return (a.center, a.radius);
+        }
+        if distance + a.radius <= b.radius {
+            return (b.center, b.radius);
+        }
+
+        let radius = (distance + a.radius + b.radius) / 2.0;
+        let center = a.center + (radius - a.radius) / distance * offset;
+
+        (center, radius)
+    }
+
+    fn importance(node: &LightNode, point: &Point3, normal: &Vec3) -> f64 {
+        let to_center = node.center - *point;
+
+        // Lights entirely below the surface can't light it.
+        if to_center.dot(*normal) < -node.radius {
+            return 0.0;
+        }
+
+        let distance_squared = to_center.length_squared();
+
+        node.power / distance_squared.max(node.radius * node.radius).max(1e-8)
+    }
+
+    /// Probabilities of going left or right from an interior node.
+    fn split(&self, left: usize, right: usize, point: &Point3, normal: &Vec3) -> Option<(f64, f64)> {
+        let left_importance = Self::importance(&self.nodes[left], point, normal);
+        let right_importance = Self::importance(&self.nodes[right], point, normal);
+        let total = left_importance + right_importance;
+
+        if total <= 0.0 || !total.is_finite() {
+            None
+        } else {
+            Some((left_importance / total, right_importance / total))
+        }
+    }
+
+    fn root(&self) -> Option<usize> {
+        self.nodes.len().checked_sub(1)
+    }
+
+    /// Picks a light for shading `point` on a surface with the given normal. Returns its index and
+    /// the probability it was picked with.
+    pub fn pick(&self, point: &Point3, normal: &Vec3) -> Option<(usize, f64)> {
+        let mut node = self.root()?;
+        if Self::importance(&self.nodes[node], point, normal) <= 0.0 {
+            return None;
+        }
+        let mut probability = 1.0;
+
+        loop {
+            match self.nodes[node].kind {
+                LightNodeKind::Leaf(index) => return Some((index, probability)),
+                LightNodeKind::Interior(left, right) => {
+                    let (left_probability, right_probability) = self.split(left, right, point, normal)?;
+
+                    if random::<f64>() < left_probability {
+                        node = left;
+                        probability *= left_probability;
+                    } else {
+                        node = right;
+                        probability *= right_probability;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Density of picking a light and then sampling `direction` towards it. `light_pdf` gives the
+    /// density of the direction for the light with the given index. Only the subtrees whose bounding
+    /// sphere lies along the direction are visited.
+    pub fn pdf<F: Fn(usize) -> f64>(&self, point: &Point3, normal: &Vec3, direction: &Vec3, light_pdf: F) -> f64 {
+        match self.root() {
+            Some(root) if Self::importance(&self.nodes[root], point, normal) > 0.0 => self.node_pdf(root, 1.0, point, normal, direction, &light_pdf),
+            _ => 0.0,
+        }
+    }
+
+    fn node_pdf<F: Fn(usize) -> f64>(&self, node: usize, probability: f64, point: &Point3, normal: &Vec3, direction: &Vec3, light_pdf: &F) -> f64 {
+        if !Self::along_ray(&self.nodes[node], point, direction) {
+            return 0.0;
+        }
+
+        match self.nodes[node].kind {
+            LightNodeKind::Leaf(index) => probability * light_pdf(index),
+            LightNodeKind::Interior(left, right) => match self.split(left, right, point, normal) {
+                Some((left_probability, right_probability)) => {
+                    self.node_pdf(left, probability * left_probability, point, normal, direction, light_pdf)
+                        + self.node_pdf(right, probability * right_probability, point, normal, direction, light_pdf)
+                }
+                None => 0.0,
+            },
+        }
+    }
+
+    /// Whether a ray from `point` along the unit `direction` passes through the node's bounding sphere.
+    fn along_ray(node: &LightNode, point: &Point3, direction: &Vec3) -> bool {
+        let to_center = node.center - *point;
+        let distance_squared = to_center.length_squared();
+        let radius_squared = node.radius * node.radius;
+
+        if distance_squared <= radius_squared {
+            return true;
+        }
+
+        let along = to_center.dot(*direction);
+
+        along > 0.0 && distance_squared - along * along <= radius_squared
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use std::sync::{Arc, OnceLock};
+use crate::data_structs::random::random;
+use crate::data_structs::ray::Ray;
+use crate::data_structs::spectrum::rgb_to_spectrum;
+use crate::data_structs::vec3::{Color, Point3, Vec3};
+use crate::lights::delta_light::DeltaLight;
+use crate::lights::environment::EnvironmentMap;
+use crate::lights::light_tree::{LightBounds, LightTree};
+use crate::lights::sky::Sky;
+use crate::lights::sphere_light::SphereLight;
+use crate::objects::hittables::HittableList;
+use crate::objects::hittables::instances::visibility::{Visibility, VisibilityFlags};
+
+pub mod delta_light;
+pub mod environment;
+pub mod ies;
+pub mod light_tree;
+pub mod sky;
+pub mod sphere_light;
+
+/// What rays that miss every object see.
+#[derive(Clone)]
+pub enum Background {
+    Color(Color),
+    Environment(Arc<EnvironmentMap>),
+    Sky(Arc<Sky>),
+}
+
+impl Background {
+    pub fn from_environment(environment: EnvironmentMap) -> Self {
+        Background::Environment(Arc::new(environment))
+    }
+
+    pub fn from_sky(sky: Sky) -> Self {
+        Background::Sky(Arc::new(sky))
+    }
+
+    pub fn value(&self, direction: &Vec3) -> Color {
+        match self {
+            Background::Color(color) => *color,
+            Background::Environment(environment) => environment.value(direction),
+            Background::Sky(sky) => sky.value(direction),
+        }
+    }
+
+    pub fn value_spectral(&self, direction: &Vec3, wavelength: f64) -> f64 {
+        rgb_to_spectrum(self.value(direction), wavelength)
+    }
+
+    /// What `ray` sees when it misses everything. Unlike `value`, it leaves out a sky's sun for rays
+    /// that were lit by it as a light already.
+    pub fn ray_value(&self, ray: &Ray) -> Color {
+        match self {
+            Background::Sky(sky) => sky.value_for(&ray.direction, ray.kind),
+            _ => self.value(&ray.direction),
+        }
+    }
+
+    pub fn ray_value_spectral(&self, ray: &Ray, wavelength: f64) -> f64 {
+        rgb_to_spectrum(self.ray_value(ray), wavelength)
+    }
+
+    /// Whether diffuse surfaces should send some of their rays towards the bright parts of the background.
+    pub fn is_importance_sampled(&self) -> bool {
+        matches!(self, Background::Environment(_) | Background::Sky(_))
+    }
+
+    pub fn sample_direction(&self) -> Vec3 {
+        match self {
+            Background::Color(_) => Vec3::ZERO,
+            Background::Environment(environment) => environment.sample_direction(),
+            Background::Sky(sky) => sky.sample_direction(),
+        }
+    }
+
+    /// Solid angle density of `sample_direction` producing `direction`.
+    pub fn pdf(&self, direction: &Vec3) -> f64 {
+        match self {
+            Background::Color(_) => 0.0,
+            Background::Environment(environment) => environment.pdf(direction),
+            Background::Sky(sky) => sky.pdf(direction),
+        }
+    }
+}
+
+impl From<Color> for Background {
+    fn from(color: Color) -> Self {
+        Background::Color(color)
+    }
+}
+
+
+/// Which light linking groups a light illuminates. Objects are put in groups with `Visibility`.
+/// Delta lights, emitters and the background can all be linked.
+#[derive(Clone, Default)]
+pub enum LightLinks {
+    #[default]
+    All,
+    Include(Vec<u32>),
+    Exclude(Vec<u32>),
+}
+
+impl LightLinks {
+    pub fn illuminates(&self, light_group: u32) -> bool {
+        match self {
+            LightLinks::All => true,
+            LightLinks::Include(groups) => groups.contains(&light_group),
+            LightLinks::Exclude(groups) => !groups.contains(&light_group),
+        }
+    }
+
+    /// Groups named by the links. Every group not named here is treated the same.
+    fn groups(&self) -> &[u32] {
+        match self {
+            LightLinks::All => &[],
+            LightLinks::Include(groups) | LightLinks::Exclude(groups) => groups,
+        }
+    }
+}
+
+/// Light trees over the emitters each light linking group sees: one for every group some emitter's
+/// links name, and one shared by all other groups.
+#[derive(Clone)]
+struct LinkedTrees {
+    named: HashMap<u32, LightTree>,
+    others: LightTree,
+}
+
+/// Lights that live next to the hittables of a scene rather than in it: delta lights, and emitters
+/// that are also in the world but should be sampled explicitly.
+#[derive(Clone, Default)]
+pub struct LightList {
+    lights: Vec<DeltaLight>,
+    /// Light linking of each delta light.
+    links: Vec<LightLinks>,
+    emitters: Vec<SphereLight>,
+    emitter_links: Vec<LightLinks>,
+    background_links: LightLinks,
+    /// Sun of the sky background, following the background's links.
+    sun: Option<DeltaLight>,
+    delta_tree: OnceLock<LightTree>,
+    emitter_trees: OnceLock<LinkedTrees>,
+}
+
+impl LightList {
+    pub fn new() -> Self {
+        LightList {
+            lights: vec![],
+            links: vec![],
+            emitters: vec![],
+            emitter_links: vec![],
+            background_links: LightLinks::All,
+            sun: None,
+            delta_tree: OnceLock::new(),
+            emitter_trees: OnceLock::new(),
+        }
+    }
+
+    pub fn add(&mut self, light: DeltaLight) {
+        self.add_linked(light, LightLinks::All);
+    }
+
+    /// Adds a light that only illuminates some light linking groups.
+    pub fn add_linked(&mut self, light: DeltaLight, links: LightLinks) {
+        self.lights.push(light);
+        self.links.push(links);
+        self.delta_tree = OnceLock::new();
+    }
+
+    /// Adds the emitter's sphere to the world and registers it so diffuse surfaces send some of their
+    /// rays towards it.
+    pub fn add_emitter(&mut self, world: &mut HittableList, emitter: SphereLight) {
+        self.add_linked_emitter(world, emitter, LightLinks::All);
+    }
+
+    /// Adds an emitter that only illuminates some light linking groups.
+    pub fn add_linked_emitter(&mut self, world: &mut HittableList, emitter: SphereLight, links: LightLinks) {
+        world.add(Visibility::new(emitter.sphere(), VisibilityFlags::ALL).with_light_links(links.clone()));
+        self.emitters.push(emitter);
+        self.emitter_links.push(links);
+        self.emitter_trees = OnceLock::new();
+    }
+
+    /// Registers the sun of `sky` as a directional light and gives the background to use with it, so
+    /// diffuse surfaces get sunlight from explicit sampling and not from chance hits on the disk.
+    pub fn add_sky(&mut self, sky: Sky) -> Background {
+        self.sun = Some(sky.sun());
+
+        Background::from_sky(sky.with_sun_as_light())
+    }
+
+    /// Limits which light linking groups the background illuminates. The camera always sees it.
+    pub fn set_background_links(&mut self, links: LightLinks) {
+        self.background_links = links;
+    }
+
+    pub fn background_links(&self) -> &LightLinks {
+        &self.background_links
+    }
+
+    fn delta_tree(&self) -> &LightTree {
+        self.delta_tree.get_or_init(|| {
+            LightTree::new(self.lights
+                .iter()
+                .enumerate()
+                .filter_map(|(index, light)| light.position().map(|center| LightBounds::new(index, center, 0.0, light.power())))
+                .collect())
+        })
+    }
+
+    /// Tree over the emitters linked to `light_group`, so picking and the pdf both leave the others out.
+    fn emitter_tree(&self, light_group: u32) -> &LightTree {
+        let trees = self.emitter_trees.get_or_init(|| {
+            let named = self.emitter_links.iter().flat_map(|links| links.groups()).copied().collect::<HashSet<u32>>();
+            // A group no links name is in no include list and no exclude list.
+            let others = |links: &LightLinks| !matches!(links, LightLinks::Include(_));
+
+            LinkedTrees {
+                named: named.into_iter().map(|group| (group, self.linked_emitter_tree(|links| links.illuminates(group)))).collect(),
+                others: self.linked_emitter_tree(others),
+            }
+        });
+
+        trees.named.get(&light_group).unwrap_or(&trees.others)
+    }
+
+    fn linked_emitter_tree<F: Fn(&LightLinks) -> bool>(&self, linked: F) -> LightTree {
+        LightTree::new(self.emitters
+            .iter()
+            .zip(self.emitter_links.iter())
+            .enumerate()
+            .filter(|(_, (_, links))| linked(links))
+            .map(|(index, (emitter, _))| LightBounds::new(index, emitter.center, emitter.radius, emitter.power()))
+            .collect())
+    }
+
+    /// Delta lights to shade `point` with, each with the probability it was picked with. Directional
+    /// lights and the sun are always returned, and one of the other lights is picked by its power and
+    /// distance. Lights not linked to `light_group` are left out.
+    pub fn sample_delta_lights(&self, point: &Point3, normal: &Vec3, light_group: u32) -> Vec<(&DeltaLight, f64)> {
+        let mut picked = self.lights
+            .iter()
+            .zip(self.links.iter())
+            .filter(|(light, links)| light.position().is_none() && links.illuminates(light_group))
+            .map(|(light, _)| (light, 1.0))
+            .collect::<Vec<(&DeltaLight, f64)>>();
+
+        if let Some(ref sun) = self.sun {
+            if self.background_links.illuminates(light_group) {
+                picked.push((sun, 1.0));
+            }
+        }
+
+        if let Some((index, probability)) = self.delta_tree().pick(point, normal) {
+            if self.links[index].illuminates(light_group) {
+                picked.push((&self.lights[index], probability));
+            }
+        }
+
+        picked
+    }
+
+    /// Whether any emitter illuminates `light_group`.
+    pub fn has_emitters(&self, light_group: u32) -> bool {
+        self.emitter_links.iter().any(|links| links.illuminates(light_group))
+    }
+
+    /// Direction from `point` towards an emitter linked to `light_group`, picked by its power and
+    /// distance. Gives `None` when no emitter can be picked.
+    pub fn sample_emitter_direction(&self, point: &Point3, normal: &Vec3, light_group: u32) -> Option<Vec3> {
+        self.emitter_tree(light_group)
+            .pick(point, normal)
+            .map(|(index, _)| self.emitters[index].sample_direction(point))
+    }
+
+    /// Solid angle density of `sample_emitter_direction` producing `direction`.
+    pub fn emitter_pdf(&self, point: &Point3, normal: &Vec3, direction: &Vec3, light_group: u32) -> f64 {
+        let direction = direction.normalize();
+
+        self.emitter_tree(light_group).pdf(point, normal, &direction, |index| self.emitters[index].pdf(point, &direction))
+    }
+}
+
+/// Uniform direction inside the cone of directions within `acos(cos_max)` of `axis`.
+pub(crate) fn sample_cone(axis: &Vec3, cos_max: f64) -> Vec3 {
+    let cos_theta = 1.0 - random::<f64>() * (1.0 - cos_max);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * random::<f64>();
+
+    let helper = if axis.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent = helper.cross(axis).normalize();
+    let bitangent = axis.cross(&tangent);
+
+    sin_theta * phi.cos() * tangent + sin_theta * phi.sin() * bitangent + cos_theta * *axis
+}
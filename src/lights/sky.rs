@@ -0,0 +1,213 @@
+use std::f64::consts::PI;
+use crate::data_structs::random::random;
+use crate::data_structs::ray::RayKind;
+use crate::data_structs::spectrum::{Spectrum, xyz_to_rgb};
+use crate::data_structs::vec3::{Color, Vec3};
+use crate::lights::delta_light::DeltaLight;
+use crate::lights::sample_cone;
+
+/// Maps the model's luminance in kcd/m² to scene units, so a clear midday sky is around one.
+const SKY_LUMINANCE_SCALE: f64 = 0.1;
+/// Angular radius of the sun disk, in radians.
+const SUN_ANGULAR_RADIUS: f64 = 0.00465;
+/// Share of the background samples spent on the sun disk.
+const SUN_SAMPLING_PROBABILITY: f64 = 0.5;
+
+/// Preetham et al. analytic daylight sky with a matching sun disk. Add it with `LightList::add_sky` to
+/// light diffuse surfaces with the sun as a directional light rather than through the disk.
+pub struct Sky {
+    sun_direction: Vec3,
+    sun_zenith_angle: f64,
+    turbidity: f64,
+    zenith: Vec3,
+    perez_luminance: [f64; 5],
+    perez_x: [f64; 5],
+    perez_y: [f64; 5],
+    intensity: f64,
+    sun_radiance: Color,
+    sun_cos_angle: f64,
+    /// Whether the sun is also in a light list, so diffuse bounces should not find it in the sky.
+    sun_is_light: bool,
+}
+
+impl Sky {
+    /// Sun elevation above the horizon and azimuth from the +x axis towards +z, in degrees.
+    /// Turbidity goes from about 2 for a very clear sky to 10 for a hazy one.
+    pub fn new(sun_elevation: f64, sun_azimuth: f64, turbidity: f64) -> Self {
+        let elevation = sun_elevation.clamp(0.0, 90.0) * PI / 180.0;
+        let azimuth = sun_azimuth * PI / 180.0;
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.cos(),
+            elevation.sin(),
+            elevation.cos() * azimuth.sin(),
+        );
+
+        let theta_s = PI / 2.0 - elevation;
+        let t = turbidity;
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+
+        let theta = [theta_s.powi(3), theta_s.powi(2), theta_s, 1.0];
+        let turbidity_terms = [t * t, t, 1.0];
+        let chromaticity = |matrix: [[f64; 4]; 3]| {
+            (0..3).map(|row| turbidity_terms[row] * (0..4).map(|col| matrix[row][col] * theta[col]).sum::<f64>()).sum::<f64>()
+        };
+
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        let sky = Self {
+            sun_direction,
+            sun_zenith_angle: theta_s,
+            turbidity,
+            zenith: Vec3::new(zenith_luminance.max(0.0), zenith_x, zenith_y),
+            perez_luminance: [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            perez_x: [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            perez_y: [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+            intensity: 1.0,
+            sun_radiance: Color::ZERO,
+            sun_cos_angle: SUN_ANGULAR_RADIUS.cos(),
+            sun_is_light: false,
+        };
+
+        sky.with_sun_irradiance(3.0)
+    }
+
+    /// Scales the sky dome.
+    pub fn with_intensity(self, intensity: f64) -> Self {
+        Self { intensity, ..self }
+    }
+
+    /// Sets how much light the sun casts on a surface facing it, before the atmosphere dims it.
+    pub fn with_sun_irradiance(self, irradiance: f64) -> Self {
+        let color = Spectrum::blackbody(5778.0, 1.0).to_rgb();
+        let sun_radiance = irradiance / self.sun_solid_angle() * color * self.atmosphere_transmittance();
+
+        Self { sun_radiance, ..self }
+    }
+
+    fn sun_solid_angle(&self) -> f64 {
+        2.0 * PI * (1.0 - self.sun_cos_angle)
+    }
+
+    /// The sun as a directional light, with the irradiance of the disk.
+    pub fn sun(&self) -> DeltaLight {
+        DeltaLight::directional(-self.sun_direction, self.sun_radiance * self.sun_solid_angle())
+    }
+
+    /// Leaves the sun disk out of what diffuse bounces see and of the importance sampling, for when
+    /// `sun` lights the scene instead.
+    pub(crate) fn with_sun_as_light(self) -> Self {
+        Self { sun_is_light: true, ..self }
+    }
+
+    fn sun_sampling_probability(&self) -> f64 {
+        if self.sun_is_light { 0.0 } else { SUN_SAMPLING_PROBABILITY }
+    }
+
+    /// Rayleigh and aerosol extinction along the path of the sunlight, for red, green and blue.
+    fn atmosphere_transmittance(&self) -> Color {
+        let zenith_degrees = self.sun_zenith_angle * 180.0 / PI;
+        let air_mass = 1.0 / (self.sun_zenith_angle.cos() + 0.50572 * (96.07995 - zenith_degrees).powf(-1.6364));
+
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let optical_depth = Color::new(0.050, 0.099, 0.224) + beta * Color::new(1.75, 2.17, 2.83);
+
+        Color::new(
+            (-air_mass * optical_depth.x).exp(),
+            (-air_mass * optical_depth.y).exp(),
+            (-air_mass * optical_depth.z).exp(),
+        )
+    }
+
+    fn perez(coefficients: &[f64; 5], cos_theta: f64, gamma: f64) -> f64 {
+        let [a, b, c, d, e] = *coefficients;
+
+        (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos() * gamma.cos())
+    }
+
+    fn sky_value(&self, direction: &Vec3) -> Color {
+        // The model is only defined above the horizon. Below it, repeat the horizon.
+        let cos_theta = direction.y.max(0.01);
+        let gamma = direction.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        let relative = |coefficients: &[f64; 5]| {
+            Sky::perez(coefficients, cos_theta, gamma) / Sky::perez(coefficients, 1.0, self.sun_zenith_angle)
+        };
+
+        let luminance = self.zenith.x * relative(&self.perez_luminance);
+        let x = self.zenith.y * relative(&self.perez_x);
+        let y = self.zenith.z * relative(&self.perez_y);
+
+        let xyz = Vec3::new(x * luminance / y, luminance, (1.0 - x - y) * luminance / y);
+        let rgb = xyz_to_rgb(xyz) * SKY_LUMINANCE_SCALE * self.intensity;
+
+        Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+    }
+
+    pub fn value(&self, direction: &Vec3) -> Color {
+        self.value_for(direction, RayKind::Camera)
+    }
+
+    /// Radiance seen by a ray of `kind`. Rays leaving diffuse surfaces miss the sun disk when the sun is
+    /// a light of its own, as they already got its light from it.
+    pub fn value_for(&self, direction: &Vec3, kind: RayKind) -> Color {
+        let direction = direction.normalize();
+        let sky = self.sky_value(&direction);
+        let lit_by_sun = self.sun_is_light && kind == RayKind::Diffuse;
+
+        if !lit_by_sun && direction.dot(self.sun_direction) >= self.sun_cos_angle {
+            sky + self.sun_radiance
+        } else {
+            sky
+        }
+    }
+
+    /// Samples the sun disk or the upper hemisphere.
+    pub fn sample_direction(&self) -> Vec3 {
+        let (cos_max, axis) = if random::<f64>() < self.sun_sampling_probability() {
+            (self.sun_cos_angle, self.sun_direction)
+        } else {
+            (0.0, Vec3::new(0.0, 1.0, 0.0))
+        };
+
+        sample_cone(&axis, cos_max)
+    }
+
+    pub fn pdf(&self, direction: &Vec3) -> f64 {
+        let direction = direction.normalize();
+
+        let sun_pdf = if direction.dot(self.sun_direction) >= self.sun_cos_angle { 1.0 / self.sun_solid_angle() } else { 0.0 };
+        let sky_pdf = if direction.y > 0.0 { 1.0 / (2.0 * PI) } else { 0.0 };
+        let sun_probability = self.sun_sampling_probability();
+
+        sun_probability * sun_pdf + (1.0 - sun_probability) * sky_pdf
+    }
+}
@@ -0,0 +1,60 @@
+use std::f64::consts::PI;
+use crate::data_structs::vec3::{Color, Point3, Vec3};
+use crate::lights::sample_cone;
+use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::Material;
+use crate::objects::hittables::sphere::Sphere;
+
+/// Spherical emitter sampled through the cone of directions it covers as seen from the shading point.
+#[derive(Clone)]
+pub struct SphereLight {
+    pub center: Point3,
+    pub radius: f64,
+    radiance: Color,
+}
+
+impl SphereLight {
+    /// Sphere glowing with `radiance` on its surface.
+    pub fn new(center: Point3, radius: f64, radiance: Color) -> Self {
+        Self { center, radius, radiance }
+    }
+
+    /// Emissive sphere the light stands for in the world.
+    pub fn sphere(&self) -> Sphere {
+        Sphere::new(self.center, self.radius, Material::DiffuseLight(DiffuseLight::from_color(self.radiance)))
+    }
+
+    pub fn power(&self) -> f64 {
+        self.radiance.luminance() * PI * 4.0 * PI * self.radius * self.radius
+    }
+
+    /// Cosine of the half angle of the cone. Points inside the sphere see it in every direction.
+    fn cos_max(&self, point: &Point3) -> f64 {
+        let distance_squared = (self.center - *point).length_squared();
+        let radius_squared = self.radius * self.radius;
+
+        if distance_squared <= radius_squared {
+            -1.0
+        } else {
+            (1.0 - radius_squared / distance_squared).sqrt()
+        }
+    }
+
+    pub fn sample_direction(&self, point: &Point3) -> Vec3 {
+        let axis = (self.center - *point).normalize();
+
+        sample_cone(&axis, self.cos_max(point))
+    }
+
+    /// Expects a unit `direction`.
+    pub fn pdf(&self, point: &Point3, direction: &Vec3) -> f64 {
+        let cos_max = self.cos_max(point);
+        let axis = (self.center - *point).normalize();
+
+        if cos_max > -1.0 && direction.dot(axis) < cos_max {
+            return 0.0;
+        }
+
+        1.0 / (2.0 * PI * (1.0 - cos_max))
+    }
+}
@@ -1,60 +1,84 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use image::RgbImage;
 use indicatif::{ProgressBar, ProgressStyle};
-use rayon::prelude::*;
 
-use rust_raytracer::data_structs::ray::ray_color;
-use rust_raytracer::data_structs::vec3::{Color, Vec3};
+use rust_raytracer::data_structs::random::{self, random};
+use rust_raytracer::data_structs::ray::{ray_color, spectral_ray_color, Ray};
+use rust_raytracer::data_structs::spectrum::{RenderMode, sample_wavelength, wavelength_to_rgb_weight};
+use rust_raytracer::data_structs::vec3::Color;
+use rust_raytracer::lights::{Background, LightList};
 use rust_raytracer::objects::camera::Camera;
-use rust_raytracer::objects::hittables::{Hittable, HittableList};
+use rust_raytracer::objects::hittables::Hittable;
+use rust_raytracer::render::{AdaptiveSampling, RenderBudget};
+use rust_raytracer::render::checkpoint::{Checkpoint, RenderSettings};
+use rust_raytracer::render::tiles::{TileOrder, TileSchedule};
 use rust_raytracer::scenes::{scene_selector, WorldEnum};
 
+const SCENE: WorldEnum = WorldEnum::FinalScene;
+// Scenes with random objects place them the same way for the same seed.
+const SCENE_SEED: u64 = 0;
+
 // Image. Change these params to get faster, but lower quality renders. const
 const ASPECT_RATIO: f64 = 1.0;
 const IMAGE_WIDTH: u32 = 800;
 const IMAGE_HEIGHT: u32 = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as u32;
 const SAMPLES_PER_PIXEL: usize = 10_000;
+// Adaptive sampling. Pixels stop between the minimum and SAMPLES_PER_PIXEL once their noise is below
+// the threshold. A threshold of zero gives every pixel SAMPLES_PER_PIXEL samples.
+const MIN_SAMPLES_PER_PIXEL: usize = 64;
+const NOISE_THRESHOLD: f64 = 0.0;
+const HEATMAP_PATH: Option<&str> = None;
+// Stop early once the time is up or the image noise is below the target, on the noise threshold's scale.
+const TIME_LIMIT: Option<Duration> = None;
+const NOISE_TARGET: Option<f64> = None;
 const MAX_DEPTH: usize = 50;
 const OUTPUT_PATH: &str = "output.png";
+const RENDER_MODE: RenderMode = RenderMode::Rgb;
+const TILE_SIZE: u32 = 32;
+const TILE_ORDER: TileOrder = TileOrder::Spiral;
+// The image and the raw render are saved every CHECKPOINT_INTERVAL and at the end. Set RESUME_PATH to
+// a checkpoint of the same scene and settings to continue it.
+const CHECKPOINT_PATH: Option<&str> = Some("output.checkpoint");
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(600);
+const RESUME_PATH: Option<&str> = None;
+
+
+fn ray_trace_pixel(camera: &dyn Camera, world: &dyn Hittable, lights: &LightList, background: &Background, x: u32, y: u32) -> Color {
+    let u = (random::<f64>() + x as f64) / (IMAGE_WIDTH - 1) as f64;
+    let v = (random::<f64>() + y as f64) / (IMAGE_HEIGHT - 1) as f64;
+    let ray = match camera.get_ray(u, v) {
+        Some(ray) => ray,
+        None => return Color::new(0.0, 0.0, 0.0),
+    };
+
+    let color = match RENDER_MODE {
+        RenderMode::Rgb => ray_color(&ray, background, world, lights, MAX_DEPTH),
+        RenderMode::Spectral => {
+            // Each sample traces one wavelength. The film converts it to RGB through its XYZ response.
+            let wavelength = sample_wavelength();
+            let ray = Ray { wavelength: Some(wavelength), ..ray };
+
+            spectral_ray_color(&ray, background, world, lights, MAX_DEPTH) * wavelength_to_rgb_weight(wavelength)
+        }
+    };
 
-
-fn ray_trace_pixel(camera: &Camera, world: &dyn Hittable, background: &Color, x: u32, y: u32) -> Color {
-    let u = (rand::random::<f64>() + x as f64) / (IMAGE_WIDTH - 1) as f64;
-    let v = (rand::random::<f64>() + y as f64) / (IMAGE_HEIGHT - 1) as f64;
-    let ray = &camera.get_ray(u, v);
-
-    ray_color(ray, background, world, MAX_DEPTH)
+    camera.exposure() * color
 }
 
-fn render_loop(image_width: u32, image_height: u32, camera: &Camera, world: &HittableList, background: &Color) -> Vec<Vec<Color>> {
-    (0..image_width)
-        .into_par_iter()
-        .rev()
-        .map(|y| (0..image_height)
-            .into_par_iter()
-            .map(|x| ray_trace_pixel(camera, world, background, x, y))
-            .collect::<Vec<Color>>())
-        .collect::<Vec<Vec<Color>>>()
-}
+/// Writes the image so far, and the checkpoint if there is one.
+fn save_progress(render: &Checkpoint) {
+    render.framebuffer.to_image().save(OUTPUT_PATH).expect("Could not save the image.");
 
-fn add_images(current_image: &Vec<Vec<Color>>, new_image: &Vec<Vec<Color>>) -> Vec<Vec<Vec3>> {
-    current_image
-        .into_par_iter()
-        .zip(new_image)
-        .map(|(current_row, new_row)| {
-            current_row
-                .into_par_iter()
-                .zip(new_row)
-                .map(|(orig_pixel, new_pixel)| (orig_pixel + new_pixel))
-                .collect::<Vec<Color>>()
-        }).collect::<Vec<Vec<Color>>>()
+    if let Some(path) = CHECKPOINT_PATH {
+        render.save(path).expect("Could not save the checkpoint.");
+    }
 }
 
 fn main() {
     // World.
-    let (background, camera, world) = scene_selector(
-        WorldEnum::FinalScene,
+    random::seed(SCENE_SEED);
+    let (background, camera, world, lights) = scene_selector(
+        SCENE,
         IMAGE_WIDTH,
         IMAGE_HEIGHT,
     );
@@ -67,24 +91,62 @@ fn main() {
     // Render loop.
     let render_time = Instant::now();
 
-    let mut pixels = vec![vec![Color::ZERO; IMAGE_WIDTH as usize]; IMAGE_HEIGHT as usize];
-    for i in 0..SAMPLES_PER_PIXEL {
-        let new_pixels = render_loop(IMAGE_HEIGHT, IMAGE_WIDTH, &camera, &world, &background);
-        pixels = add_images(&pixels, &new_pixels);
-
-        // Generate image from vector of pixels.
-        let mut buffer = RgbImage::new(IMAGE_WIDTH, IMAGE_HEIGHT);
-        for (y, row) in pixels.iter().enumerate() {
-            for (x, color) in row.iter().enumerate() {
-                buffer.put_pixel(x as u32, y as u32, color.write_color(i + 1));
-            }
-        }
-        buffer.save(OUTPUT_PATH).expect("TODO: panic message");
-        // {
-        //     Err(e) => eprintln!("Error writing file: {e}"),
-        //     Ok(()) => println!("Render saved to: {OUTPUT_PATH}"),
-        // };  
+    let tiles = TileSchedule::new(IMAGE_WIDTH, IMAGE_HEIGHT, TILE_SIZE, TILE_ORDER);
+    let sampling = AdaptiveSampling::new(MIN_SAMPLES_PER_PIXEL as u32, SAMPLES_PER_PIXEL as u32, NOISE_THRESHOLD);
+    let mut budget = RenderBudget::default();
+    if let Some(time_limit) = TIME_LIMIT {
+        budget = budget.with_time_limit(time_limit);
+    }
+    if let Some(noise_target) = NOISE_TARGET {
+        budget = budget.with_noise_target(noise_target);
+    }
+
+    let settings = RenderSettings {
+        scene: format!("{SCENE:?}"),
+        scene_seed: SCENE_SEED,
+        width: IMAGE_WIDTH,
+        height: IMAGE_HEIGHT,
+        max_depth: MAX_DEPTH as u32,
+        render_mode: format!("{RENDER_MODE:?}"),
+        samples_per_pixel: SAMPLES_PER_PIXEL as u32,
+        min_samples_per_pixel: MIN_SAMPLES_PER_PIXEL as u32,
+        noise_threshold: NOISE_THRESHOLD,
+        tile_size: TILE_SIZE,
+        tile_order: format!("{TILE_ORDER:?}"),
+    };
+    let mut render = match RESUME_PATH {
+        Some(path) => Checkpoint::resume(path, &settings).expect("Could not resume from the checkpoint."),
+        None => Checkpoint::new(settings, rand::random()),
+    };
+    progress_bar.set_position(render.passes as u64);
+
+    let mut last_save = Instant::now();
+    while render.passes < SAMPLES_PER_PIXEL as u32 {
+        let pass_time = Instant::now();
+        let (seed, pass) = (render.seed, render.passes as u64);
+
+        // The framebuffer starts at the top of the image, the camera at the bottom. Each sample draws its
+        // random numbers from the seed, pass and pixel, so a resumed render carries on with new ones.
+        let active_pixels = tiles.render_pass(&mut render.framebuffer, &sampling, |x, y| {
+            random::seed(seed ^ (pass << 32) ^ (y * IMAGE_WIDTH + x) as u64);
+            ray_trace_pixel(camera.as_ref(), &world, &lights, &background, x, IMAGE_HEIGHT - 1 - y)
+        });
+        render.passes += 1;
         progress_bar.inc(1);
+
+        if active_pixels == 0 || budget.is_spent(render_time.elapsed(), pass_time.elapsed(), &render.framebuffer) {
+            break;
+        }
+
+        if last_save.elapsed() >= CHECKPOINT_INTERVAL {
+            save_progress(&render);
+            last_save = Instant::now();
+        }
+    }
+    save_progress(&render);
+
+    if let Some(path) = HEATMAP_PATH {
+        render.framebuffer.sample_heatmap(SAMPLES_PER_PIXEL as u32).save(path).expect("Could not save the sample heatmap.");
     }
 
 
@@ -92,5 +154,6 @@ fn main() {
     let render_time = render_time.elapsed();
     println!("Done.");
     println!("Render time: {:?}", render_time);
+    println!("Image noise: {:.4}", render.framebuffer.noise());
 
 }
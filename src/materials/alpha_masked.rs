@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use crate::data_structs::random::random;
+use crate::data_structs::ray::Ray;
+use crate::data_structs::vec3::{Color, Point3};
+use crate::materials::{Emmitable, Maskable, Material, Scatterable};
+use crate::materials::textures::Texture;
+use crate::objects::hittables::HitRecord;
+
+#[derive(Copy, Clone)]
+pub enum AlphaMode {
+    /// Hits with an opacity below the threshold are ignored.
+    Threshold(f64),
+    /// Hits are kept with a probability equal to the opacity.
+    Stochastic,
+}
+
+/// Wraps a material and cuts it out where the opacity texture is transparent.
+#[derive(Clone)]
+pub struct AlphaMasked {
+    material: Box<Material>,
+    opacity: Arc<dyn Texture + Send + Sync>,
+    mode: AlphaMode,
+}
+
+impl AlphaMasked {
+    pub fn new<T: Texture + 'static>(material: Material, opacity: T, mode: AlphaMode) -> Self {
+        Self { material: Box::new(material), opacity: Arc::new(opacity), mode }
+    }
+}
+
+impl Scatterable for AlphaMasked {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        self.material.scatter(ray, record, attenuation, scattered)
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, record: &HitRecord, scattered: &Ray) -> f64 {
+        self.material.scattering_pdf(ray, record, scattered)
+    }
+}
+
+impl Emmitable for AlphaMasked {
+    fn emitted(&self, ray: &Ray, record: &HitRecord) -> Color {
+        self.material.emitted(ray, record)
+    }
+
+    fn emitted_spectral(&self, ray: &Ray, record: &HitRecord, wavelength: f64) -> f64 {
+        self.material.emitted_spectral(ray, record, wavelength)
+    }
+}
+
+impl Maskable for AlphaMasked {
+    fn is_opaque(&self, u: f64, v: f64, p: &Point3) -> bool {
+        let value = self.opacity.value(u, v, p);
+        let alpha = (value.x + value.y + value.z) / 3.0;
+
+        let visible = match self.mode {
+            AlphaMode::Threshold(threshold) => alpha >= threshold,
+            AlphaMode::Stochastic => random::<f64>() < alpha,
+        };
+
+        visible && self.material.is_opaque(u, v, p)
+    }
+}
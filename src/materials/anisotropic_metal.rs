@@ -0,0 +1,117 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+use crate::data_structs::random::random;
+use crate::data_structs::ray::Ray;
+use crate::data_structs::vec3::{Color, Vec3};
+use crate::materials::Scatterable;
+use crate::materials::textures::solid_color::SolidColor;
+use crate::materials::textures::Texture;
+use crate::objects::hittables::HitRecord;
+
+/// Conductor with an anisotropic GGX microfacet distribution, e.g. brushed metal.
+/// `roughness_u` applies along the surface tangent and `roughness_v` along the bitangent.
+#[derive(Clone)]
+pub struct AnisotropicMetal {
+    albedo: Arc<dyn Texture + Send + Sync>,
+    roughness_u: f64,
+    roughness_v: f64,
+    rotation: Option<Arc<dyn Texture + Send + Sync>>,
+}
+
+impl AnisotropicMetal {
+    pub fn from_texture<T: Texture + 'static>(texture: T, roughness_u: f64, roughness_v: f64) -> Self {
+        Self {
+            albedo: Arc::new(texture),
+            roughness_u: roughness_u.clamp(1e-3, 1.0),
+            roughness_v: roughness_v.clamp(1e-3, 1.0),
+            rotation: None,
+        }
+    }
+
+    pub fn from_color(albedo: Color, roughness_u: f64, roughness_v: f64) -> Self {
+        Self::from_texture(SolidColor::new(albedo.x, albedo.y, albedo.z), roughness_u, roughness_v)
+    }
+
+    /// Rotates the tangent frame around the normal. A texture value of one is a full turn.
+    pub fn with_rotation<T: Texture + 'static>(self, rotation: T) -> Self {
+        Self { rotation: Some(Arc::new(rotation)), ..self }
+    }
+
+    /// Smith masking term for a direction in the local shading frame.
+    fn smith_g1(&self, local: Vec3) -> f64 {
+        let cos_squared = local.z * local.z;
+        if cos_squared <= 0.0 {
+            return 0.0;
+        }
+
+        let alpha_tan_squared = (self.roughness_u * self.roughness_u * local.x * local.x
+            + self.roughness_v * self.roughness_v * local.y * local.y) / cos_squared;
+        let lambda = (-1.0 + (1.0 + alpha_tan_squared).sqrt()) / 2.0;
+
+        1.0 / (1.0 + lambda)
+    }
+
+    /// Samples a microfacet normal proportionally to D(m) * cos(theta_m), in the local shading frame.
+    fn sample_microfacet_normal(&self) -> Vec3 {
+        let xi_1 = random::<f64>();
+        let xi_2 = random::<f64>();
+
+        let phi = f64::atan2(
+            self.roughness_v * (2.0 * PI * xi_1).sin(),
+            self.roughness_u * (2.0 * PI * xi_1).cos(),
+        );
+        let (sin_phi, cos_phi) = phi.sin_cos();
+
+        let alpha_squared = 1.0 / (cos_phi * cos_phi / (self.roughness_u * self.roughness_u)
+            + sin_phi * sin_phi / (self.roughness_v * self.roughness_v));
+        let tan_theta_squared = alpha_squared * xi_2 / (1.0 - xi_2);
+        let cos_theta = 1.0 / (1.0 + tan_theta_squared).sqrt();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+
+        Vec3::new(sin_theta * cos_phi, sin_theta * sin_phi, cos_theta)
+    }
+}
+
+impl Scatterable for AnisotropicMetal {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        let normal = record.normal;
+        let mut tangent = record.tangent();
+        let mut bitangent = record.bitangent();
+
+        if let Some(rotation) = &self.rotation {
+            let value = rotation.value(record.u, record.v, &record.point);
+            let (sin_angle, cos_angle) = (2.0 * PI * (value.x + value.y + value.z) / 3.0).sin_cos();
+
+            (tangent, bitangent) = (
+                cos_angle * tangent + sin_angle * bitangent,
+                -sin_angle * tangent + cos_angle * bitangent,
+            );
+        }
+
+        let to_local = |vector: Vec3| Vec3::new(vector.dot(tangent), vector.dot(bitangent), vector.dot(normal));
+
+        let outgoing = to_local(-ray.direction.normalize());
+        if outgoing.z <= 0.0 {
+            return false;
+        }
+
+        let microfacet_normal = self.sample_microfacet_normal();
+        let outgoing_dot_microfacet = outgoing.dot(microfacet_normal);
+        let incoming = Vec3::reflect(-outgoing, microfacet_normal);
+        if incoming.z <= 0.0 || outgoing_dot_microfacet <= 0.0 {
+            return false;
+        }
+
+        // Schlick Fresnel with the albedo as reflectance at normal incidence.
+        let f0 = self.albedo.value(record.u, record.v, &record.point);
+        let fresnel = f0 + (Color::ONE - f0) * (1.0 - outgoing_dot_microfacet).powi(5);
+        let shadowing = self.smith_g1(incoming) * self.smith_g1(outgoing);
+
+        let direction = incoming.x * tangent + incoming.y * bitangent + incoming.z * normal;
+
+        *scattered = Ray::new(record.point, direction, ray.time);
+        *attenuation = fresnel * shadowing * outgoing_dot_microfacet / (outgoing.z * microfacet_normal.z);
+
+        true
+    }
+}
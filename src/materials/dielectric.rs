@@ -1,16 +1,24 @@
+use crate::data_structs::random::random;
 use crate::data_structs::ray::Ray;
 use crate::data_structs::vec3::{Color, Vec3};
 use crate::materials::Scatterable;
+use crate::materials::thin_film::ThinFilm;
 use crate::objects::hittables::HitRecord;
 
 #[derive(Copy, Clone)]
 pub struct Dielectric {
     index_of_refraction: f64,
+    thin_film: Option<ThinFilm>,
 }
 
 impl Dielectric {
     pub fn new(index_of_refraction: f64) -> Self {
-        Dielectric { index_of_refraction }
+        Dielectric { index_of_refraction, thin_film: None }
+    }
+
+    /// Coats the surface with a film of the given thickness in nanometers.
+    pub fn with_thin_film(self, thickness: f64, film_ior: f64) -> Self {
+        Dielectric { thin_film: Some(ThinFilm::new(thickness, film_ior)), ..self }
     }
 
     pub fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
@@ -34,9 +42,32 @@ impl Scatterable for Dielectric {
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let random_double = rand::random::<f64>();
-        let reflectance = Dielectric::reflectance(cos_theta, refraction_ratio);
-        let direction = if cannot_refract || reflectance > random_double {
+        let random_double = random::<f64>();
+
+        let reflect = if cannot_refract {
+            true
+        } else if let Some(film) = self.thin_film {
+            // The film reflects each channel differently. Pick a branch by the average and reweight.
+            let (outer_ior, substrate_ior) = if record.front_face {
+                (1.0, self.index_of_refraction)
+            } else {
+                (self.index_of_refraction, 1.0)
+            };
+            let reflectance = film.reflectance(cos_theta, outer_ior, Color::ONE * substrate_ior, ray.wavelength);
+            let probability = ((reflectance.x + reflectance.y + reflectance.z) / 3.0).clamp(1e-3, 1.0 - 1e-3);
+
+            if probability > random_double {
+                *attenuation = reflectance / probability;
+                true
+            } else {
+                *attenuation = (Color::ONE - reflectance) / (1.0 - probability);
+                false
+            }
+        } else {
+            Dielectric::reflectance(cos_theta, refraction_ratio) > random_double
+        };
+
+        let direction = if reflect {
             Vec3::reflect(unit_direction, record.normal)
         } else {
             Vec3::refract(unit_direction, record.normal, refraction_ratio)
@@ -1,29 +1,41 @@
-use std::sync::Arc;
-use crate::data_structs::vec3::{Color, Point3};
-use crate::materials::{Emmitable};
-use crate::materials::textures::solid_color::SolidColor;
-
+use crate::data_structs::ray::Ray;
+use crate::data_structs::spectrum::Spectrum;
+use crate::data_structs::vec3::Color;
+use crate::materials::Emmitable;
+use crate::materials::emission::Emission;
 use crate::materials::textures::Texture;
+use crate::objects::hittables::HitRecord;
 
 #[derive(Clone)]
 pub struct DiffuseLight {
-    emit: Arc<dyn Texture + Send + Sync>,
+    emission: Emission,
 }
 
 impl DiffuseLight {
+    pub fn from_emission(emission: Emission) -> Self {
+        Self { emission }
+    }
+
     pub fn from_texture<T: Texture + 'static>(texture: T) -> Self {
-        Self {
-            emit: Arc::new(texture)
-        }
+        Self::from_emission(Emission::from_texture(texture, 1.0))
     }
 
     pub fn from_color(albedo: Color) -> Self {
-        Self::from_texture(SolidColor::new(albedo.x, albedo.y, albedo.z))
+        Self::from_emission(Emission::from_color(albedo, 1.0))
+    }
+
+    /// Emits a spectrum. The RGB render mode uses its RGB equivalent.
+    pub fn from_spectrum(spectrum: Spectrum) -> Self {
+        Self::from_emission(Emission::from_spectrum(spectrum, 1.0))
     }
 }
 
 impl Emmitable for DiffuseLight {
-    fn emitted(&self, u: f64, v: f64, p: &Point3) -> Point3 {
-        self.emit.value(u, v, p)
+    fn emitted(&self, ray: &Ray, record: &HitRecord) -> Color {
+        self.emission.radiance(ray, record)
+    }
+
+    fn emitted_spectral(&self, ray: &Ray, record: &HitRecord, wavelength: f64) -> f64 {
+        self.emission.radiance_spectral(ray, record, wavelength)
     }
 }
@@ -0,0 +1,93 @@
+use crate::data_structs::ray::Ray;
+use crate::data_structs::spectrum::{sample_wavelength, wavelength_to_rgb_weight};
+use crate::data_structs::vec3::Color;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::Scatterable;
+use crate::objects::hittables::HitRecord;
+
+/// Index of refraction as a function of wavelength. Coefficients use wavelengths in micrometers.
+#[derive(Copy, Clone)]
+pub enum Dispersion {
+    /// n = a + b / λ²
+    Cauchy { a: f64, b: f64 },
+    /// n² = 1 + Σ bᵢ λ² / (λ² - cᵢ)
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    pub fn index_of_refraction(&self, wavelength: f64) -> f64 {
+        let micrometers = wavelength / 1000.0;
+        let squared = micrometers * micrometers;
+
+        match *self {
+            Dispersion::Cauchy { a, b } => a + b / squared,
+            Dispersion::Sellmeier { b, c } => {
+                let n_squared = 1.0 + (0..3)
+                    .map(|i| b[i] * squared / (squared - c[i]))
+                    .sum::<f64>();
+
+                n_squared.sqrt()
+            }
+        }
+    }
+}
+
+/// Dielectric whose index of refraction depends on the wavelength of the path.
+/// The first hit on a path without a wavelength picks one, and the path keeps it afterwards.
+#[derive(Copy, Clone)]
+pub struct DispersiveDielectric {
+    dispersion: Dispersion,
+}
+
+impl DispersiveDielectric {
+    pub fn new(dispersion: Dispersion) -> Self {
+        Self { dispersion }
+    }
+
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        Self::new(Dispersion::Cauchy { a, b })
+    }
+
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Self::new(Dispersion::Sellmeier { b, c })
+    }
+
+    /// Schott N-BK7 borosilicate crown glass.
+    pub fn bk7() -> Self {
+        Self::sellmeier(
+            [1.03961212, 0.231792344, 1.01046945],
+            [0.00600069867, 0.0200179144, 103.560653],
+        )
+    }
+
+    pub fn fused_silica() -> Self {
+        Self::sellmeier(
+            [0.6961663, 0.4079426, 0.8974794],
+            [0.00467914826, 0.0135120631, 97.9340025],
+        )
+    }
+
+    pub fn diamond() -> Self {
+        Self::sellmeier([0.3306, 4.3356, 0.0], [0.030625, 0.011236, 0.0])
+    }
+}
+
+impl Scatterable for DispersiveDielectric {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        let (wavelength, weight) = match ray.wavelength {
+            Some(wavelength) => (wavelength, Color::ONE),
+            None => {
+                let wavelength = sample_wavelength();
+                (wavelength, wavelength_to_rgb_weight(wavelength))
+            }
+        };
+
+        let index_of_refraction = self.dispersion.index_of_refraction(wavelength);
+        Dielectric::new(index_of_refraction).scatter(ray, record, attenuation, scattered);
+
+        scattered.wavelength = Some(wavelength);
+        *attenuation *= weight;
+
+        true
+    }
+}
@@ -0,0 +1,142 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+use crate::data_structs::ray::Ray;
+use crate::data_structs::spectrum::{rgb_to_spectrum, Spectrum};
+use crate::data_structs::vec3::{Color, Point3};
+use crate::lights::ies::IesProfile;
+use crate::materials::{Emmitable, Maskable, Material, Scatterable};
+use crate::materials::textures::solid_color::SolidColor;
+use crate::materials::textures::Texture;
+use crate::objects::hittables::HitRecord;
+
+/// Light given off by a surface: a color or spectrum scaled by a strength,
+/// optionally modulated by a texture and restricted to the front face.
+#[derive(Clone)]
+pub struct Emission {
+    color: Arc<dyn Texture + Send + Sync>,
+    spectrum: Option<Spectrum>,
+    strength: f64,
+    multiplier: Option<Arc<dyn Texture + Send + Sync>>,
+    two_sided: bool,
+    profile: Option<Arc<IesProfile>>,
+}
+
+impl Emission {
+    /// `strength` scales the texture to the emitted radiance.
+    pub fn from_texture<T: Texture + 'static>(texture: T, strength: f64) -> Self {
+        Self {
+            color: Arc::new(texture),
+            spectrum: None,
+            strength,
+            multiplier: None,
+            two_sided: true,
+            profile: None,
+        }
+    }
+
+    pub fn from_color(color: Color, strength: f64) -> Self {
+        Self::from_texture(SolidColor::new(color.x, color.y, color.z), strength)
+    }
+
+    /// Emits a spectrum. The RGB render mode uses its RGB equivalent.
+    pub fn from_spectrum(spectrum: Spectrum, strength: f64) -> Self {
+        Self {
+            spectrum: Some(spectrum.clone()),
+            ..Self::from_color(spectrum.to_rgb(), strength)
+        }
+    }
+
+    /// Emitter of a total power in watts spread evenly over a surface with the given area. It only emits
+    /// from the front face, as a two-sided surface would give off twice the power.
+    pub fn from_power(color: Color, watts: f64, area: f64) -> Self {
+        Self::from_color(color, watts / (PI * area)).one_sided()
+    }
+
+    /// Only emit from the front face, the side the outward normal points to.
+    pub fn one_sided(self) -> Self {
+        Self { two_sided: false, ..self }
+    }
+
+    /// Scales the emission by a texture, e.g. to paint an image on a screen.
+    pub fn with_multiplier<T: Texture + 'static>(self, multiplier: T) -> Self {
+        Self { multiplier: Some(Arc::new(multiplier)), ..self }
+    }
+
+    /// Shapes the emission with a measured fixture distribution, aimed along the outward normal. The
+    /// strength then scales the candela values of the profile per unit area, the same way the intensity
+    /// of a delta light with a profile does.
+    pub fn with_profile(self, profile: IesProfile) -> Self {
+        Self { profile: Some(Arc::new(profile)), ..self }
+    }
+
+    fn scale(&self, ray: &Ray, record: &HitRecord) -> Color {
+        if !self.two_sided && !record.front_face {
+            return Color::ZERO;
+        }
+
+        let strength = match self.profile {
+            Some(ref profile) => {
+                let outward_normal = if record.front_face { record.normal } else { -record.normal };
+                self.strength * profile.projected_candela(&-ray.direction, &outward_normal)
+            }
+            None => self.strength,
+        };
+
+        match self.multiplier {
+            Some(ref multiplier) => strength * multiplier.value(record.u, record.v, &record.point),
+            None => Color::ONE * strength,
+        }
+    }
+
+    pub fn radiance(&self, ray: &Ray, record: &HitRecord) -> Color {
+        self.scale(ray, record) * self.color.value(record.u, record.v, &record.point)
+    }
+
+    pub fn radiance_spectral(&self, ray: &Ray, record: &HitRecord, wavelength: f64) -> f64 {
+        let scale = rgb_to_spectrum(self.scale(ray, record), wavelength);
+
+        match self.spectrum {
+            Some(ref spectrum) => scale * spectrum.value(wavelength),
+            None => scale * rgb_to_spectrum(self.color.value(record.u, record.v, &record.point), wavelength),
+        }
+    }
+}
+
+/// Adds emission to any material, e.g. a glowing screen behind a reflective glass layer.
+#[derive(Clone)]
+pub struct Emissive {
+    material: Box<Material>,
+    emission: Emission,
+}
+
+impl Emissive {
+    pub fn new(material: Material, emission: Emission) -> Self {
+        Self { material: Box::new(material), emission }
+    }
+}
+
+impl Scatterable for Emissive {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        self.material.scatter(ray, record, attenuation, scattered)
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, record: &HitRecord, scattered: &Ray) -> f64 {
+        self.material.scattering_pdf(ray, record, scattered)
+    }
+}
+
+impl Emmitable for Emissive {
+    fn emitted(&self, ray: &Ray, record: &HitRecord) -> Color {
+        self.emission.radiance(ray, record) + self.material.emitted(ray, record)
+    }
+
+    fn emitted_spectral(&self, ray: &Ray, record: &HitRecord, wavelength: f64) -> f64 {
+        self.emission.radiance_spectral(ray, record, wavelength) + self.material.emitted_spectral(ray, record, wavelength)
+    }
+}
+
+impl Maskable for Emissive {
+    fn is_opaque(&self, u: f64, v: f64, p: &Point3) -> bool {
+        self.material.is_opaque(u, v, p)
+    }
+}
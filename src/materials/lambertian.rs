@@ -1,3 +1,4 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
 use crate::data_structs::ray::Ray;
 use crate::data_structs::vec3::{Color, random_unit_vector};
@@ -35,4 +36,10 @@ impl Scatterable for Lambertian {
 
         true
     }
+
+    fn scattering_pdf(&self, _ray: &Ray, record: &HitRecord, scattered: &Ray) -> f64 {
+        let cosine = record.normal.dot(scattered.direction.normalize());
+
+        if cosine < 0.0 { 0.0 } else { cosine / PI }
+    }
 }
\ No newline at end of file
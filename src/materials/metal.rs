@@ -1,12 +1,14 @@
 use crate::data_structs::ray::Ray;
 use crate::data_structs::vec3::{Color, random_in_unit_sphere, Vec3};
 use crate::materials::Scatterable;
+use crate::materials::thin_film::ThinFilm;
 use crate::objects::hittables::HitRecord;
 
 #[derive(Clone)]
 pub struct Metal {
     albedo: Color,
     fuzz: f64,
+    thin_film: Option<ThinFilm>,
 }
 
 impl Metal {
@@ -17,7 +19,22 @@ impl Metal {
             1.0
         };
 
-        Metal { albedo, fuzz: fuzz_limited }
+        Metal { albedo, fuzz: fuzz_limited, thin_film: None }
+    }
+
+    /// Coats the metal with a film of the given thickness in nanometers, e.g. an oxide layer.
+    pub fn with_thin_film(self, thickness: f64, film_ior: f64) -> Self {
+        Metal { thin_film: Some(ThinFilm::new(thickness, film_ior)), ..self }
+    }
+
+    /// Real index of refraction that gives the albedo as reflectance at normal incidence.
+    fn equivalent_ior(&self) -> Color {
+        let ior = |reflectance: f64| {
+            let root = reflectance.clamp(0.0, 0.99).sqrt();
+            (1.0 + root) / (1.0 - root)
+        };
+
+        Color::new(ior(self.albedo.x), ior(self.albedo.y), ior(self.albedo.z))
     }
 }
 
@@ -26,7 +43,13 @@ impl Scatterable for Metal {
         let reflected = Vec3::reflect(ray.direction.normalize(), record.normal);
 
         *scattered = Ray::new(record.point, reflected + self.fuzz * random_in_unit_sphere(), ray.time);
-        *attenuation = self.albedo;
+        *attenuation = match self.thin_film {
+            Some(film) => {
+                let cos_theta = -ray.direction.normalize().dot(record.normal);
+                film.reflectance(cos_theta, 1.0, self.equivalent_ior(), ray.wavelength)
+            }
+            None => self.albedo,
+        };
 
         scattered.direction.dot(record.normal) > 0.0
     }
@@ -1,6 +1,14 @@
 use crate::data_structs::ray::Ray;
+use crate::data_structs::spectrum::rgb_to_spectrum;
 use crate::data_structs::vec3::{Color, Point3};
+use crate::materials::alpha_masked::AlphaMasked;
+use crate::materials::anisotropic_metal::AnisotropicMetal;
 use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::dispersive_dielectric::DispersiveDielectric;
+use crate::materials::emission::Emissive;
+use crate::materials::normal_mapped::NormalMapped;
+use crate::materials::subsurface::Subsurface;
+use crate::materials::thin_dielectric::ThinDielectric;
 use crate::materials::textures::isotropic::Isotropic;
 use crate::objects::hittables::HitRecord;
 
@@ -13,6 +21,14 @@ pub mod metal;
 pub mod dielectric;
 pub mod textures;
 pub mod diffuse_light;
+pub mod normal_mapped;
+pub mod alpha_masked;
+pub mod subsurface;
+pub mod dispersive_dielectric;
+pub mod anisotropic_metal;
+pub mod thin_film;
+pub mod thin_dielectric;
+pub mod emission;
 
 #[derive(Clone)]
 pub enum Material {
@@ -21,6 +37,13 @@ pub enum Material {
     Dielectric(Dielectric),
     DiffuseLight(DiffuseLight),
     Isotropic(Isotropic),
+    NormalMapped(NormalMapped),
+    AlphaMasked(AlphaMasked),
+    Subsurface(Subsurface),
+    DispersiveDielectric(DispersiveDielectric),
+    AnisotropicMetal(AnisotropicMetal),
+    ThinDielectric(ThinDielectric),
+    Emissive(Emissive),
 }
 
 impl Default for Material {
@@ -31,10 +54,27 @@ impl Default for Material {
 
 pub trait Scatterable {
     fn scatter(&self, ray: &Ray, record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool;
+
+    /// Density of the material sampling `scattered`, for materials whose attenuation is the ratio of
+    /// their scattering function to this density. Lights can then share the sampling with them.
+    /// Zero means the material cannot be combined with light sampling, like specular materials.
+    fn scattering_pdf(&self, _ray: &Ray, _record: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
 }
 
 pub trait Emmitable {
-    fn emitted(&self, u: f64, v: f64, p: &Point3) -> Color;
+    fn emitted(&self, ray: &Ray, record: &HitRecord) -> Color;
+
+    /// Emitted radiance at a single wavelength in nanometers, used by the spectral render mode.
+    fn emitted_spectral(&self, ray: &Ray, record: &HitRecord, wavelength: f64) -> f64 {
+        rgb_to_spectrum(self.emitted(ray, record), wavelength)
+    }
+}
+
+/// Decides whether a surface point blocks rays. Transparent points are skipped during intersection.
+pub trait Maskable {
+    fn is_opaque(&self, u: f64, v: f64, p: &Point3) -> bool;
 }
 
 impl Scatterable for Material {
@@ -44,18 +84,62 @@ impl Scatterable for Material {
             Material::Metal(ref inner) => inner.scatter(ray, record, attenuation, scattered),
             Material::Dielectric(ref inner) => inner.scatter(ray, record, attenuation, scattered),
             Material::Isotropic(ref inner) => inner.scatter(ray, record, attenuation, scattered),
+            Material::NormalMapped(ref inner) => inner.scatter(ray, record, attenuation, scattered),
+            Material::AlphaMasked(ref inner) => inner.scatter(ray, record, attenuation, scattered),
+            Material::Subsurface(ref inner) => inner.scatter(ray, record, attenuation, scattered),
+            Material::DispersiveDielectric(ref inner) => inner.scatter(ray, record, attenuation, scattered),
+            Material::AnisotropicMetal(ref inner) => inner.scatter(ray, record, attenuation, scattered),
+            Material::ThinDielectric(ref inner) => inner.scatter(ray, record, attenuation, scattered),
+            Material::Emissive(ref inner) => inner.scatter(ray, record, attenuation, scattered),
 
             _ => false,
         }
     }
+
+    fn scattering_pdf(&self, ray: &Ray, record: &HitRecord, scattered: &Ray) -> f64 {
+        match *self {
+            Material::Lambertian(ref inner) => inner.scattering_pdf(ray, record, scattered),
+            Material::NormalMapped(ref inner) => inner.scattering_pdf(ray, record, scattered),
+            Material::AlphaMasked(ref inner) => inner.scattering_pdf(ray, record, scattered),
+            Material::Emissive(ref inner) => inner.scattering_pdf(ray, record, scattered),
+
+            _ => 0.0,
+        }
+    }
 }
 
 impl Emmitable for Material {
-    fn emitted(&self, u: f64, v: f64, p: &Color) -> Color {
+    fn emitted(&self, ray: &Ray, record: &HitRecord) -> Color {
         match *self {
-            Material::DiffuseLight(ref inner) => inner.emitted(u, v, p),
+            Material::DiffuseLight(ref inner) => inner.emitted(ray, record),
+            Material::NormalMapped(ref inner) => inner.emitted(ray, record),
+            Material::AlphaMasked(ref inner) => inner.emitted(ray, record),
+            Material::Emissive(ref inner) => inner.emitted(ray, record),
 
             _ => Color::new(0.0, 0.0, 0.0)
         }
     }
-}
\ No newline at end of file
+
+    fn emitted_spectral(&self, ray: &Ray, record: &HitRecord, wavelength: f64) -> f64 {
+        match *self {
+            Material::DiffuseLight(ref inner) => inner.emitted_spectral(ray, record, wavelength),
+            Material::NormalMapped(ref inner) => inner.emitted_spectral(ray, record, wavelength),
+            Material::AlphaMasked(ref inner) => inner.emitted_spectral(ray, record, wavelength),
+            Material::Emissive(ref inner) => inner.emitted_spectral(ray, record, wavelength),
+
+            _ => 0.0
+        }
+    }
+}
+
+impl Maskable for Material {
+    fn is_opaque(&self, u: f64, v: f64, p: &Point3) -> bool {
+        match *self {
+            Material::AlphaMasked(ref inner) => inner.is_opaque(u, v, p),
+            Material::NormalMapped(ref inner) => inner.is_opaque(u, v, p),
+            Material::Emissive(ref inner) => inner.is_opaque(u, v, p),
+
+            _ => true,
+        }
+    }
+}
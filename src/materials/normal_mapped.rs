@@ -0,0 +1,50 @@
+use crate::data_structs::ray::Ray;
+use crate::data_structs::vec3::{Color, Point3};
+use crate::materials::{Emmitable, Maskable, Material, Scatterable};
+use crate::materials::textures::normal_map::NormalMap;
+use crate::objects::hittables::HitRecord;
+
+/// Wraps a material and shades it with a normal from a normal or bump map.
+#[derive(Clone)]
+pub struct NormalMapped {
+    material: Box<Material>,
+    normal_map: NormalMap,
+}
+
+impl NormalMapped {
+    pub fn new(material: Material, normal_map: NormalMap) -> Self {
+        Self { material: Box::new(material), normal_map }
+    }
+}
+
+impl Scatterable for NormalMapped {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        let mut shading_record = record.clone();
+        shading_record.normal = self.normal_map.perturb(record);
+
+        self.material.scatter(ray, &shading_record, attenuation, scattered)
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, record: &HitRecord, scattered: &Ray) -> f64 {
+        let mut shading_record = record.clone();
+        shading_record.normal = self.normal_map.perturb(record);
+
+        self.material.scattering_pdf(ray, &shading_record, scattered)
+    }
+}
+
+impl Emmitable for NormalMapped {
+    fn emitted(&self, ray: &Ray, record: &HitRecord) -> Color {
+        self.material.emitted(ray, record)
+    }
+
+    fn emitted_spectral(&self, ray: &Ray, record: &HitRecord, wavelength: f64) -> f64 {
+        self.material.emitted_spectral(ray, record, wavelength)
+    }
+}
+
+impl Maskable for NormalMapped {
+    fn is_opaque(&self, u: f64, v: f64, p: &Point3) -> bool {
+        self.material.is_opaque(u, v, p)
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use crate::data_structs::ray::Ray;
+use crate::data_structs::vec3::{Color, random_in_unit_sphere};
+use crate::materials::dielectric::Dielectric;
+use crate::materials::Scatterable;
+use crate::materials::textures::solid_color::SolidColor;
+use crate::materials::textures::Texture;
+use crate::objects::hittables::HitRecord;
+
+/// What happened at a hit reported by a `SubsurfaceMedium`.
+#[derive(Copy, Clone)]
+pub enum SubsurfaceEvent {
+    /// The ray reached the boundary from the outside.
+    Entry,
+    /// The ray scattered inside the volume. Holds the path weight of the free flight.
+    Scatter(Color),
+    /// The ray reached the boundary from the inside. Holds the path weight of the free flight.
+    Exit(Color),
+}
+
+/// Random walk subsurface scattering inside a closed boundary.
+/// Use it through `SubsurfaceMedium`, which tracks where the walk is.
+#[derive(Clone)]
+pub struct Subsurface {
+    albedo: Arc<dyn Texture + Send + Sync>,
+    scattering_radius: Color,
+    boundary: Dielectric,
+    event: SubsurfaceEvent,
+}
+
+impl Subsurface {
+    /// `scattering_radius` is the mean free path per color channel, in world units.
+    pub fn from_texture<T: Texture + 'static>(texture: T, scattering_radius: Color, index_of_refraction: f64) -> Self {
+        Self {
+            albedo: Arc::new(texture),
+            scattering_radius,
+            boundary: Dielectric::new(index_of_refraction),
+            event: SubsurfaceEvent::Entry,
+        }
+    }
+
+    pub fn from_color(albedo: Color, scattering_radius: Color, index_of_refraction: f64) -> Self {
+        Self::from_texture(SolidColor::new(albedo.x, albedo.y, albedo.z), scattering_radius, index_of_refraction)
+    }
+
+    pub fn with_event(&self, event: SubsurfaceEvent) -> Self {
+        Self { event, ..self.clone() }
+    }
+
+    /// Extinction coefficient per color channel.
+    pub fn density(&self) -> Color {
+        Color::new(
+            1.0 / self.scattering_radius.x.max(1e-4),
+            1.0 / self.scattering_radius.y.max(1e-4),
+            1.0 / self.scattering_radius.z.max(1e-4),
+        )
+    }
+}
+
+impl Scatterable for Subsurface {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        match self.event {
+            SubsurfaceEvent::Entry => self.boundary.scatter(ray, record, attenuation, scattered),
+            SubsurfaceEvent::Exit(weight) => {
+                let refracted = self.boundary.scatter(ray, record, attenuation, scattered);
+                *attenuation *= weight;
+
+                refracted
+            }
+            SubsurfaceEvent::Scatter(weight) => {
+                *scattered = Ray::new(record.point, random_in_unit_sphere(), ray.time);
+                *attenuation = weight * self.albedo.value(record.u, record.v, &record.point);
+
+                true
+            }
+        }
+    }
+}
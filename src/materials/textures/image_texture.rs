@@ -23,7 +23,29 @@ impl ImageTexture {
 
         let image_width = img.width();
         let image_height = img.height();
-        let data = img.into_bytes();
+        let data = img.to_rgb8().into_raw();
+
+        Self {
+            data,
+            width: image_width,
+            height: image_height,
+            bytes_per_scanline: BYTES_PER_PIXEL * image_width,
+        }
+    }
+
+    /// Loads the alpha channel of an image as a grayscale texture, e.g. for cutout masks.
+    pub fn from_alpha_channel(filename: &str) -> Self {
+        let img = ImageReader::open(filename)
+            .expect("Image texture file not found.")
+            .decode()
+            .unwrap();
+
+        let image_width = img.width();
+        let image_height = img.height();
+        let data = img.to_rgba8()
+            .pixels()
+            .flat_map(|pixel| [pixel[3]; BYTES_PER_PIXEL as usize])
+            .collect();
 
         Self {
             data,
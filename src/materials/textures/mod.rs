@@ -3,6 +3,7 @@ pub mod checker_texture;
 pub mod perlin;
 pub mod image_texture;
 pub mod isotropic;
+pub mod normal_map;
 
 use dyn_clonable::dyn_clone::DynClone;
 use crate::data_structs::vec3::{Color, Point3};
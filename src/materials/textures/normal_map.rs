@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use crate::data_structs::vec3::Vec3;
+use crate::materials::textures::image_texture::ImageTexture;
+use crate::materials::textures::Texture;
+use crate::objects::hittables::HitRecord;
+
+/// Step in texture space used to estimate the derivatives of a bump map.
+const BUMP_DELTA: f64 = 0.0005;
+
+#[derive(Clone)]
+pub enum NormalMap {
+    /// RGB encoded normals in the tangent frame of the surface.
+    TangentSpace(Arc<dyn Texture + Send + Sync>),
+    /// Scalar height field. The average of the texture channels is used as the height.
+    Bump {
+        height: Arc<dyn Texture + Send + Sync>,
+        scale: f64,
+    },
+}
+
+impl NormalMap {
+    pub fn from_image(filename: &str) -> Self {
+        Self::from_texture(ImageTexture::new(filename))
+    }
+
+    pub fn from_texture<T: Texture + 'static>(texture: T) -> Self {
+        NormalMap::TangentSpace(Arc::new(texture))
+    }
+
+    pub fn bump<T: Texture + 'static>(height: T, scale: f64) -> Self {
+        NormalMap::Bump { height: Arc::new(height), scale }
+    }
+
+    /// Returns the shading normal for the hit, on the same side as `record.normal`.
+    pub fn perturb(&self, record: &HitRecord) -> Vec3 {
+        let normal = match self {
+            NormalMap::TangentSpace(texture) => {
+                let encoded = texture.value(record.u, record.v, &record.point);
+                let local = 2.0 * encoded - Vec3::ONE;
+
+                local.x * record.tangent() + local.y * record.bitangent() + local.z * record.normal
+            }
+            NormalMap::Bump { height, scale } => {
+                let sample_height = |u: f64, v: f64, offset: Vec3| {
+                    let value = height.value(u, v, &(record.point + offset));
+                    (value.x + value.y + value.z) / 3.0
+                };
+
+                let center = sample_height(record.u, record.v, Vec3::ZERO);
+                let shifted_u = sample_height(record.u + BUMP_DELTA, record.v, BUMP_DELTA * record.dpdu);
+                let shifted_v = sample_height(record.u, record.v + BUMP_DELTA, BUMP_DELTA * record.dpdv);
+
+                let height_du = scale * (shifted_u - center) / BUMP_DELTA;
+                let height_dv = scale * (shifted_v - center) / BUMP_DELTA;
+
+                let dpdu = record.dpdu + height_du * record.normal;
+                let dpdv = record.dpdv + height_dv * record.normal;
+
+                dpdu.cross(&dpdv)
+            }
+        };
+
+        if normal.near_zero() {
+            return record.normal;
+        }
+
+        let normal = normal.normalize();
+        if normal.dot(record.normal) < 0.0 {
+            -normal
+        } else {
+            normal
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use rand::thread_rng;
 use rand::seq::SliceRandom;
 
+use crate::data_structs::random::rng;
 use crate::data_structs::vec3::{Color, Point3, Vec3};
 use crate::materials::textures::Texture;
 
@@ -93,7 +93,7 @@ impl Perlin {
 
     fn generate_permutation(point_count: i32) -> Vec<i32> {
         let mut permutation: Vec<i32> = (0..point_count).collect();
-        permutation.shuffle(&mut thread_rng());
+        permutation.shuffle(&mut rng());
 
         permutation
     }
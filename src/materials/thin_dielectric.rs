@@ -0,0 +1,55 @@
+use crate::data_structs::random::random;
+use crate::data_structs::ray::Ray;
+use crate::data_structs::vec3::{Color, Vec3};
+use crate::materials::dielectric::Dielectric;
+use crate::materials::Scatterable;
+use crate::materials::thin_film::ThinFilm;
+use crate::objects::hittables::HitRecord;
+
+/// A single thin sheet of a dielectric, like a window pane or a soap bubble.
+/// Transmitted rays pass straight through, since both faces bend them by the same amount.
+#[derive(Copy, Clone)]
+pub struct ThinDielectric {
+    index_of_refraction: f64,
+    thickness: Option<f64>,
+}
+
+impl ThinDielectric {
+    pub fn new(index_of_refraction: f64) -> Self {
+        Self { index_of_refraction, thickness: None }
+    }
+
+    /// Makes the sheet thin enough for interference, with a thickness in nanometers.
+    pub fn with_thin_film(self, thickness: f64) -> Self {
+        Self { thickness: Some(thickness), ..self }
+    }
+}
+
+impl Scatterable for ThinDielectric {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        let unit_direction = ray.direction.normalize();
+        let cos_theta = f64::min(-unit_direction.dot(record.normal), 1.0);
+
+        let reflectance = match self.thickness {
+            Some(thickness) => ThinFilm::new(thickness, self.index_of_refraction)
+                .reflectance(cos_theta, 1.0, Color::ONE, ray.wavelength),
+            None => {
+                // Sum over the light bouncing back and forth inside the sheet.
+                let single = Dielectric::reflectance(cos_theta, self.index_of_refraction);
+                Color::ONE * (2.0 * single / (1.0 + single))
+            }
+        };
+
+        let probability = ((reflectance.x + reflectance.y + reflectance.z) / 3.0).clamp(1e-3, 1.0 - 1e-3);
+        let direction = if probability > random::<f64>() {
+            *attenuation = reflectance / probability;
+            Vec3::reflect(unit_direction, record.normal)
+        } else {
+            *attenuation = (Color::ONE - reflectance) / (1.0 - probability);
+            unit_direction
+        };
+
+        *scattered = Ray::new(record.point, direction, ray.time);
+        true
+    }
+}
@@ -0,0 +1,81 @@
+use std::f64::consts::PI;
+use crate::data_structs::spectrum::rgb_to_spectrum;
+use crate::data_structs::vec3::Color;
+
+/// Wavelengths in nanometers standing in for the red, green and blue channels.
+const RGB_WAVELENGTHS: [f64; 3] = [650.0, 550.0, 450.0];
+
+/// A thin coating that makes reflections iridescent through interference.
+#[derive(Copy, Clone)]
+pub struct ThinFilm {
+    /// Film thickness in nanometers.
+    pub thickness: f64,
+    pub index_of_refraction: f64,
+}
+
+impl ThinFilm {
+    pub fn new(thickness: f64, index_of_refraction: f64) -> Self {
+        Self { thickness, index_of_refraction }
+    }
+
+    /// Reflectance of the film between a medium with index `outer_ior` and a substrate with
+    /// index `substrate_ior`, for light arriving at `cos_theta` from the outer medium.
+    pub fn reflectance_at(&self, cos_theta: f64, outer_ior: f64, substrate_ior: f64, wavelength: f64) -> f64 {
+        let film_ior = self.index_of_refraction;
+        let cos_outer = cos_theta.clamp(0.0, 1.0);
+        let sin_outer_squared = 1.0 - cos_outer * cos_outer;
+
+        let sin_film_squared = (outer_ior / film_ior).powi(2) * sin_outer_squared;
+        if sin_film_squared >= 1.0 {
+            return 1.0;
+        }
+        let cos_film = (1.0 - sin_film_squared).sqrt();
+
+        let sin_substrate_squared = (outer_ior / substrate_ior).powi(2) * sin_outer_squared;
+        if sin_substrate_squared >= 1.0 {
+            return 1.0;
+        }
+        let cos_substrate = (1.0 - sin_substrate_squared).sqrt();
+
+        // Amplitude reflection coefficients at both interfaces, for s and p polarization.
+        let s_outer = fresnel_s(outer_ior, cos_outer, film_ior, cos_film);
+        let p_outer = fresnel_p(outer_ior, cos_outer, film_ior, cos_film);
+        let s_inner = fresnel_s(film_ior, cos_film, substrate_ior, cos_substrate);
+        let p_inner = fresnel_p(film_ior, cos_film, substrate_ior, cos_substrate);
+
+        let phase = 4.0 * PI * film_ior * self.thickness * cos_film / wavelength;
+
+        (airy(s_outer, s_inner, phase) + airy(p_outer, p_inner, phase)) / 2.0
+    }
+
+    /// Reflectance per color channel. A ray that carries a wavelength gets a gray value for it.
+    pub fn reflectance(&self, cos_theta: f64, outer_ior: f64, substrate_ior: Color, wavelength: Option<f64>) -> Color {
+        match wavelength {
+            Some(wavelength) => {
+                let substrate = rgb_to_spectrum(substrate_ior, wavelength);
+                Color::ONE * self.reflectance_at(cos_theta, outer_ior, substrate, wavelength)
+            }
+            None => Color::new(
+                self.reflectance_at(cos_theta, outer_ior, substrate_ior.x, RGB_WAVELENGTHS[0]),
+                self.reflectance_at(cos_theta, outer_ior, substrate_ior.y, RGB_WAVELENGTHS[1]),
+                self.reflectance_at(cos_theta, outer_ior, substrate_ior.z, RGB_WAVELENGTHS[2]),
+            ),
+        }
+    }
+}
+
+fn fresnel_s(n1: f64, cos_1: f64, n2: f64, cos_2: f64) -> f64 {
+    (n1 * cos_1 - n2 * cos_2) / (n1 * cos_1 + n2 * cos_2)
+}
+
+fn fresnel_p(n1: f64, cos_1: f64, n2: f64, cos_2: f64) -> f64 {
+    (n2 * cos_1 - n1 * cos_2) / (n2 * cos_1 + n1 * cos_2)
+}
+
+/// Reflectance of a layer from the amplitude coefficients of its two interfaces.
+fn airy(r_outer: f64, r_inner: f64, phase: f64) -> f64 {
+    let cross = 2.0 * r_outer * r_inner * phase.cos();
+
+    (r_outer * r_outer + r_inner * r_inner + cross)
+        / (1.0 + r_outer * r_outer * r_inner * r_inner + cross)
+}
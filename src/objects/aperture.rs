@@ -0,0 +1,154 @@
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
+use std::sync::Arc;
+use rand::Rng;
+use crate::data_structs::random::{rng, SeededRng};
+use crate::data_structs::vec3::{Point3, Vec3};
+use crate::materials::textures::Texture;
+
+/// Outline of the lens opening, which is the shape out of focus highlights take.
+#[derive(Clone)]
+pub enum ApertureShape {
+    Circle,
+    /// Regular polygon formed by the aperture blades. `rotation` is in degrees.
+    Polygon { blades: u32, rotation: f64 },
+    /// Grayscale mask over a square inside the lens. Brighter texels let more light through.
+    Texture(Arc<ApertureMask>),
+}
+
+/// Texels of an aperture texture along one side of the table it is sampled from.
+const MASK_RESOLUTION: usize = 256;
+
+/// Aperture texture tabulated into a distribution over its texels.
+pub struct ApertureMask {
+    /// Running sum of the texel luminances, row by row from `v` of zero.
+    cdf: Vec<f64>,
+    transmission: f64,
+}
+
+impl ApertureMask {
+    pub fn new<T: Texture>(texture: &T) -> Self {
+        let texel = |index: usize| (index as f64 + 0.5) / MASK_RESOLUTION as f64;
+
+        let mut total = 0.0;
+        let mut cdf = Vec::with_capacity(MASK_RESOLUTION * MASK_RESOLUTION);
+        for j in 0..MASK_RESOLUTION {
+            for i in 0..MASK_RESOLUTION {
+                let (u, v) = (texel(i), texel(j));
+                total += texture.value(u, v, &Point3::new(u * 2.0 - 1.0, v * 2.0 - 1.0, 0.0)).luminance().clamp(0.0, 1.0);
+                cdf.push(total);
+            }
+        }
+
+        ApertureMask { cdf, transmission: total / (MASK_RESOLUTION * MASK_RESOLUTION) as f64 }
+    }
+
+    /// Fraction of the mask that lets light through.
+    pub fn transmission(&self) -> f64 {
+        self.transmission
+    }
+
+    /// Point picked in proportion to how much light the mask lets through there, on the square inscribed
+    /// in the unit disk. `None` when the mask is closed everywhere.
+    fn sample(&self, rng: &mut SeededRng) -> Option<Vec3> {
+        let total = *self.cdf.last()?;
+        if total <= 0.0 {
+            return None;
+        }
+
+        let target = rng.gen::<f64>() * total;
+        let cell = self.cdf.partition_point(|&sum| sum <= target).min(self.cdf.len() - 1);
+        let u = ((cell % MASK_RESOLUTION) as f64 + rng.gen::<f64>()) / MASK_RESOLUTION as f64;
+        let v = ((cell / MASK_RESOLUTION) as f64 + rng.gen::<f64>()) / MASK_RESOLUTION as f64;
+
+        Some(FRAC_1_SQRT_2 * Vec3::new(u * 2.0 - 1.0, v * 2.0 - 1.0, 0.0))
+    }
+}
+
+/// Lens opening sampled by the camera for depth of field.
+#[derive(Clone)]
+pub struct Aperture {
+    shape: ApertureShape,
+    squeeze: f64,
+    cat_eye: f64,
+}
+
+impl Aperture {
+    pub fn circle() -> Self {
+        Self { shape: ApertureShape::Circle, squeeze: 1.0, cat_eye: 0.0 }
+    }
+
+    pub fn polygon(blades: u32, rotation: f64) -> Self {
+        Self { shape: ApertureShape::Polygon { blades: blades.max(3), rotation }, ..Self::circle() }
+    }
+
+    /// Samples the texture over `u` and `v` from zero to one, on the square that fits inside the lens.
+    /// The darker the mask, the less light reaches the film, see `transmission`.
+    pub fn from_texture<T: Texture>(texture: T) -> Self {
+        Self { shape: ApertureShape::Texture(Arc::new(ApertureMask::new(&texture))), ..Self::circle() }
+    }
+
+    /// Anamorphic squeeze factor. Out of focus highlights become `squeeze` times taller than wide.
+    pub fn with_squeeze(self, squeeze: f64) -> Self {
+        Self { squeeze, ..self }
+    }
+
+    /// Cuts the opening with the lens barrel towards the image edges, so highlights there turn into
+    /// cat's eyes. Zero disables it, one cuts highlights in the corners down to half their width.
+    /// The light the barrel blocks is lost, so the edges of the image darken too.
+    pub fn with_cat_eye(self, cat_eye: f64) -> Self {
+        Self { cat_eye, ..self }
+    }
+
+    /// Fraction of the light through an open lens of the same size that this opening lets through.
+    /// Only textures block part of it.
+    pub fn transmission(&self) -> f64 {
+        match self.shape {
+            ApertureShape::Texture(ref mask) => mask.transmission(),
+            _ => 1.0,
+        }
+    }
+
+    /// Point on the opening, scaled to fit the unit disk, for the image coordinates `(s, t)`. `None`
+    /// when the lens barrel blocks the point, or a texture lets no light through.
+    pub fn sample(&self, s: f64, t: f64) -> Option<Vec3> {
+        // Offset of the barrel opening. It moves away from the aperture as the image point leaves the center.
+        let barrel = self.cat_eye * Vec3::new(s - 0.5, t - 0.5, 0.0) * 2.0_f64.sqrt();
+
+        let point = self.sample_shape()?;
+        if (point - barrel).length_squared() > 1.0 {
+            return None;
+        }
+
+        Some(Vec3::new(point.x / self.squeeze, point.y, 0.0))
+    }
+
+    fn sample_shape(&self) -> Option<Vec3> {
+        let mut rng = rng();
+
+        match self.shape {
+            ApertureShape::Circle => Some(Vec3::random_in_unit_disk()),
+            ApertureShape::Polygon { blades, rotation } => {
+                // Pick one of the triangles between the center and an edge, then a point inside it.
+                let blade = rng.gen_range(0..blades) as f64;
+                let angle = |corner: f64| rotation.to_radians() + 2.0 * PI * corner / blades as f64;
+                let a = Vec3::new(angle(blade).cos(), angle(blade).sin(), 0.0);
+                let b = Vec3::new(angle(blade + 1.0).cos(), angle(blade + 1.0).sin(), 0.0);
+
+                let (mut x, mut y) = (rng.gen::<f64>(), rng.gen::<f64>());
+                if x + y > 1.0 {
+                    x = 1.0 - x;
+                    y = 1.0 - y;
+                }
+
+                Some(x * a + y * b)
+            }
+            ApertureShape::Texture(ref mask) => mask.sample(&mut rng),
+        }
+    }
+}
+
+impl Default for Aperture {
+    fn default() -> Self {
+        Aperture::circle()
+    }
+}
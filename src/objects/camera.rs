@@ -1,23 +1,87 @@
 use std::f64::consts::PI;
 
-use rand::{Rng, thread_rng};
+use rand::Rng;
 
+use crate::data_structs::random::rng;
 use crate::data_structs::ray::Ray;
 use crate::data_structs::vec3::{Point3, Vec3};
+use crate::objects::aperture::Aperture;
 
-pub struct Camera {
+/// How the camera maps image coordinates to rays.
+#[derive(Copy, Clone)]
+pub enum Projection {
+    /// Rays spread out from the lens, covering the field of view.
+    Perspective,
+    /// Parallel rays leave a view plane of a fixed size, so objects keep their size with distance.
+    Orthographic,
+    /// Full sphere of directions in latitude-longitude layout, centered on the viewing direction.
+    Equirectangular,
+    /// Six 90° faces in a 3x2 grid. The top row holds right, left and up, the bottom row down,
+    /// front and back. Use a 3:2 image for square faces.
+    Cubemap,
+    /// Circular fisheye fitting the image height, covering `fov` radians across the circle.
+    Fisheye { mapping: FisheyeMapping, fov: f64, aspect_ratio: f64 },
+}
+
+/// How the distance from the center of a fisheye image relates to the angle off the viewing direction.
+#[derive(Copy, Clone)]
+pub enum FisheyeMapping {
+    /// Distance proportional to the angle, as used for dome projection.
+    Equidistant,
+    /// Equal areas on the image cover equal solid angles.
+    Equisolid,
+}
+
+/// Where each eye goes in a stereo image.
+#[derive(Copy, Clone)]
+pub enum StereoLayout {
+    /// Left eye on the left half, right eye on the right half.
+    SideBySide,
+    /// Left eye on the top half, right eye on the bottom half.
+    OverUnder,
+}
+
+#[derive(Copy, Clone)]
+struct Stereo {
+    eye_separation: f64,
+    convergence: f64,
+    layout: StereoLayout,
+}
+
+/// Turns image coordinates into the rays the render loop traces.
+pub trait Camera {
+    /// Ray through the point `(s, t)` of the image, both going from zero to one. `t` goes up.
+    /// `None` when the lens blocks the sampled path, which counts as a black sample. This is how
+    /// vignetting shows up.
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray>;
+
+    /// Scale applied to the radiance gathered through the camera.
+    fn exposure(&self) -> f64 {
+        1.0
+    }
+}
+
+/// Camera built from a viewing direction and one of the `Projection`s.
+#[derive(Clone)]
+pub struct ProjectionCamera {
+    projection: Projection,
+    stereo: Option<Stereo>,
     origin: Point3,
     horizontal: Vec3,
     vertical: Vec3,
     lower_left_corner: Vec3,
     u: Vec3,
     v: Vec3,
+    w: Vec3,
     lens_radius: f64,
+    aperture: Aperture,
+    focus_dist: f64,
+    focal_plane_normal: Vec3,
     start_time: f64,
     end_time: f64,
 }
 
-impl Camera {
+impl ProjectionCamera {
     pub fn new(
         look_from: Point3,
         look_at: Point3,
@@ -45,28 +109,282 @@ impl Camera {
 
         let lens_radius = aperture / 2.0;
 
-        Camera {
+        ProjectionCamera {
+            projection: Projection::Perspective,
             origin,
             horizontal,
             vertical,
             lower_left_corner,
             u,
             v,
+            w,
             lens_radius,
+            aperture: Aperture::circle(),
+            focus_dist,
+            focal_plane_normal: w,
             start_time,
             end_time,
+            stereo: None,
+        }
+    }
+
+    /// Orthographic camera looking along `look_at - look_from`. `view_height` is the height of the
+    /// visible part of the scene in world units. Everything is sharp until `with_focus` gives it a lens.
+    pub fn orthographic(look_from: Point3, look_at: Point3, up_vector: Vec3, view_height: f64, aspect_ratio: f64) -> Self {
+        let origin = look_from;
+        let view_width = aspect_ratio * view_height;
+
+        let w = (look_from - look_at).normalize();
+        let u = up_vector.cross(&w).normalize();
+        let v = w.cross(&u);
+
+        let horizontal = view_width * u;
+        let vertical = view_height * v;
+        let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0;
+
+        ProjectionCamera {
+            projection: Projection::Orthographic,
+            origin,
+            horizontal,
+            vertical,
+            lower_left_corner,
+            u,
+            v,
+            w,
+            lens_radius: 0.0,
+            aperture: Aperture::circle(),
+            focus_dist: (look_at - look_from).length(),
+            focal_plane_normal: w,
+            start_time: 0.0,
+            end_time: 1.0,
+            stereo: None,
+        }
+    }
+
+    /// Panoramic camera at `look_from`, with `look_at` in the center of the image.
+    fn panoramic(projection: Projection, look_from: Point3, look_at: Point3, up_vector: Vec3) -> Self {
+        let w = (look_from - look_at).normalize();
+        let u = up_vector.cross(&w).normalize();
+        let v = w.cross(&u);
+
+        ProjectionCamera {
+            projection,
+            origin: look_from,
+            horizontal: Vec3::ZERO,
+            vertical: Vec3::ZERO,
+            lower_left_corner: Vec3::ZERO,
+            u,
+            v,
+            w,
+            lens_radius: 0.0,
+            aperture: Aperture::circle(),
+            focus_dist: 1.0,
+            focal_plane_normal: w,
+            start_time: 0.0,
+            end_time: 1.0,
+            stereo: None,
+        }
+    }
+
+    /// 360° latitude-longitude panorama. Use a 2:1 image.
+    pub fn equirectangular(look_from: Point3, look_at: Point3, up_vector: Vec3) -> Self {
+        Self::panoramic(Projection::Equirectangular, look_from, look_at, up_vector)
+    }
+
+    /// Six-face cubemap in a 3x2 layout. Use a 3:2 image.
+    pub fn cubemap(look_from: Point3, look_at: Point3, up_vector: Vec3) -> Self {
+        Self::panoramic(Projection::Cubemap, look_from, look_at, up_vector)
+    }
+
+    /// Circular fisheye covering `fov` degrees across the image height. Outside the circle the mapping
+    /// keeps going until it looks straight back.
+    pub fn fisheye(look_from: Point3, look_at: Point3, up_vector: Vec3, fov: f64, mapping: FisheyeMapping, aspect_ratio: f64) -> Self {
+        let projection = Projection::Fisheye { mapping, fov: fov * PI / 180.0, aspect_ratio };
+
+        Self::panoramic(projection, look_from, look_at, up_vector)
+    }
+
+    /// Direction for image coordinates in the camera frame, where `x` points right, `y` up and `z`
+    /// forward. Only used by the panoramic projections.
+    fn panoramic_direction(&self, s: f64, t: f64) -> (f64, f64, f64) {
+        match self.projection {
+            Projection::Equirectangular => {
+                let longitude = (s - 0.5) * 2.0 * PI;
+                let latitude = (t - 0.5) * PI;
+
+                (latitude.cos() * longitude.sin(), latitude.sin(), latitude.cos() * longitude.cos())
+            }
+            Projection::Cubemap => {
+                let column = ((s * 3.0) as usize).min(2);
+                let top_row = t >= 0.5;
+
+                // Position on the face, from -1 to 1.
+                let a = (s * 3.0 - column as f64) * 2.0 - 1.0;
+                let b = ((t * 2.0) % 1.0) * 2.0 - 1.0;
+                let b = if t >= 1.0 { 1.0 } else { b };
+
+                match (top_row, column) {
+                    (true, 0) => (1.0, b, -a),
+                    (true, 1) => (-1.0, b, a),
+                    (true, _) => (a, 1.0, -b),
+                    (false, 0) => (a, -1.0, b),
+                    (false, 1) => (a, b, 1.0),
+                    (false, _) => (-a, b, -1.0),
+                }
+            }
+            Projection::Fisheye { mapping, fov, aspect_ratio } => {
+                let x = (s - 0.5) * 2.0 * aspect_ratio;
+                let y = (t - 0.5) * 2.0;
+                let radius = (x * x + y * y).sqrt();
+
+                let theta = match mapping {
+                    FisheyeMapping::Equidistant => radius * fov / 2.0,
+                    FisheyeMapping::Equisolid => 2.0 * (radius * (fov / 4.0).sin()).min(1.0).asin(),
+                }.min(PI);
+                let phi = y.atan2(x);
+
+                (theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos())
+            }
+            Projection::Perspective | Projection::Orthographic => (0.0, 0.0, 1.0),
         }
     }
 
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disk();
+    /// Lens of `aperture` diameter, blurring everything away from the plane at `focus_dist`. Perspective
+    /// cameras keep their field of view.
+    pub fn with_focus(self, aperture: f64, focus_dist: f64) -> Self {
+        let (horizontal, vertical, lower_left_corner) = match self.projection {
+            Projection::Perspective => {
+                let scale = focus_dist / self.focus_dist;
+                (scale * self.horizontal, scale * self.vertical, self.origin + scale * (self.lower_left_corner - self.origin))
+            }
+            _ => (self.horizontal, self.vertical, self.lower_left_corner),
+        };
+
+        ProjectionCamera { lens_radius: aperture / 2.0, focus_dist, horizontal, vertical, lower_left_corner, ..self }
+    }
+
+    /// Time interval the shutter is open for, which moving objects blur across.
+    pub fn with_shutter(self, start_time: f64, end_time: f64) -> Self {
+        ProjectionCamera { start_time, end_time, ..self }
+    }
+
+    /// Shape of the lens opening. Its size still comes from the aperture the camera was built with.
+    pub fn with_aperture(self, aperture: Aperture) -> Self {
+        ProjectionCamera { aperture, ..self }
+    }
+
+    /// Shifts the lens parallel to the sensor by a fraction of the image width and height, moving the
+    /// view without turning the camera. Keeps vertical lines parallel when pointing the camera level and
+    /// shifting up to frame a building.
+    pub fn with_shift(self, horizontal: f64, vertical: f64) -> Self {
+        let lower_left_corner = self.lower_left_corner + horizontal * self.horizontal + vertical * self.vertical;
+
+        ProjectionCamera { lower_left_corner, ..self }
+    }
+
+    /// Tilts the plane in focus of a perspective camera, in degrees. A positive `vertical` tilt leans the
+    /// top of the plane away from the camera, so it can follow the ground, and a positive `horizontal`
+    /// tilt does the same with the right side. Tilting against the ground leaves a narrow sharp band for a
+    /// miniature look. The plane still passes through the focus distance at the center of the view.
+    pub fn with_tilt(self, vertical: f64, horizontal: f64) -> Self {
+        let focal_plane_normal = self.w + (vertical * PI / 180.0).tan() * self.v + (horizontal * PI / 180.0).tan() * self.u;
+
+        ProjectionCamera { focal_plane_normal, ..self }
+    }
+
+    /// Renders both eyes into one image, each eye getting half of it, so build the camera with the
+    /// aspect ratio of a single eye. The eyes are `eye_separation` apart and their views line up at the
+    /// `convergence` distance, which can be infinite. Equirectangular cameras become omni-directional
+    /// stereo, where the eyes circle around the camera position as the view turns.
+    pub fn with_stereo(self, eye_separation: f64, convergence: f64, layout: StereoLayout) -> Self {
+        ProjectionCamera { stereo: Some(Stereo { eye_separation, convergence, layout }), ..self }
+    }
+
+    /// Ray for an eye moved `eye_offset` to the right of the camera position.
+    fn get_eye_ray(&self, s: f64, t: f64, eye_offset: f64, convergence: f64) -> Option<Ray> {
+        let eye = eye_offset * self.u;
+        // Pinhole cameras skip the aperture, so its barrel cannot block them.
+        let rd = if self.lens_radius > 0.0 { self.lens_radius * self.aperture.sample(s, t)? } else { Vec3::ZERO };
         let offset = self.u * rd.x + self.v * rd.y;
-        let mut rng = thread_rng();
+        let mut rng = rng();
+        let time = rng.gen_range(self.start_time..self.end_time);
+
+        match self.projection {
+            Projection::Perspective => {
+                // Shifting the view window with the eye keeps the eyes parallel, and shifting it back
+                // in proportion to the focus distance makes them line up at the convergence distance.
+                let window_shift = eye * (1.0 - self.focus_dist / convergence);
+                let origin = self.origin + eye;
+                let direction = self.lower_left_corner + window_shift + s * self.horizontal + t * self.vertical - origin;
+
+                // The view window lies on the untilted focal plane. With a tilt, follow the pixel's direction
+                // until it meets the tilted plane instead, or focus at infinity if it never does. The plane
+                // goes through the focus distance in front of the camera centre, so both eyes share it.
+                let plane_point = self.origin - self.focus_dist * self.w;
+                let along_normal = direction.dot(self.focal_plane_normal);
+                let distance = (plane_point - origin).dot(self.focal_plane_normal) / along_normal;
+                if along_normal >= 0.0 || distance <= 0.0 {
+                    return Some(Ray::new(origin + offset, direction, time));
+                }
+                let focus_point = origin + distance * direction;
+
+                Some(Ray::new(origin + offset, focus_point - origin - offset, time))
+            }
+            Projection::Orthographic => {
+                let view_point = self.lower_left_corner + s * self.horizontal + t * self.vertical + eye;
+                let focus_point = if convergence.is_finite() {
+                    view_point + self.focus_dist / convergence * (-convergence * self.w - eye)
+                } else {
+                    view_point - self.focus_dist * self.w
+                };
+
+                Some(Ray::new(view_point + offset, focus_point - view_point - offset, time))
+            }
+            Projection::Equirectangular | Projection::Cubemap | Projection::Fisheye { .. } => {
+                let (x, y, z) = self.panoramic_direction(s, t);
+                let direction = x * self.u + y * self.v - z * self.w;
+
+                // Omni-directional stereo: the eyes sit on a circle, perpendicular to the horizontal view.
+                let eye = match self.projection {
+                    Projection::Equirectangular => {
+                        let longitude = (s - 0.5) * 2.0 * PI;
+                        eye_offset * (longitude.cos() * self.u + longitude.sin() * self.w)
+                    }
+                    _ => eye,
+                };
 
-        Ray::new(
-            self.origin + offset,
-            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
-            rng.gen_range(self.start_time..self.end_time),
-        )
+                let direction = if convergence.is_finite() {
+                    convergence * direction.normalize() - eye
+                } else {
+                    direction
+                };
+
+                Some(Ray::new(self.origin + eye, direction, time))
+            }
+        }
+    }
+}
+
+impl Camera for ProjectionCamera {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let stereo = match self.stereo {
+            Some(stereo) => stereo,
+            None => return self.get_eye_ray(s, t, 0.0, f64::INFINITY),
+        };
+
+        // Positive for the right eye, in units of half the eye separation.
+        let (eye, s, t) = match stereo.layout {
+            StereoLayout::SideBySide if s < 0.5 => (-1.0, s * 2.0, t),
+            StereoLayout::SideBySide => (1.0, s * 2.0 - 1.0, t),
+            StereoLayout::OverUnder if t >= 0.5 => (-1.0, s, t * 2.0 - 1.0),
+            StereoLayout::OverUnder => (1.0, s, t * 2.0),
+        };
+
+        self.get_eye_ray(s, t, eye * stereo.eye_separation / 2.0, stereo.convergence)
     }
-}
\ No newline at end of file
+
+    /// A textured aperture lets through only part of the light, pinholes skip it.
+    fn exposure(&self) -> f64 {
+        if self.lens_radius > 0.0 { self.aperture.transmission() } else { 1.0 }
+    }
+}
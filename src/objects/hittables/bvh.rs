@@ -2,8 +2,8 @@ use std::cmp::Ordering;
 use std::sync::Arc;
 
 use rand::distributions::uniform::SampleRange;
-use rand::thread_rng;
 
+use crate::data_structs::random::rng;
 use crate::data_structs::ray::Ray;
 use crate::objects::aabb::AABB;
 use crate::objects::hittables::{HitRecord, Hittable, HittableList};
@@ -48,7 +48,7 @@ impl BVHNode {
     }
 
     pub fn new(hittables: Vec<Arc<dyn Hittable + Send + Sync>>, start: usize, end: usize, time0: f64, time1: f64) -> Self {
-        let mut rng = thread_rng();
+        let mut rng = rng();
 
         let mut objects = hittables.clone();
         let axis = (0..3).sample_single(&mut rng);
@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use rand::random;
+use crate::data_structs::random::random;
 use crate::data_structs::ray::Ray;
 use crate::data_structs::vec3::Color;
 use crate::materials::Material;
@@ -9,6 +9,11 @@ use crate::materials::textures::Texture;
 use crate::objects::aabb::AABB;
 use crate::objects::hittables::{HitRecord, Hittable};
 
+/// Samples how far a ray travels through a homogeneous medium before it scatters.
+pub fn free_flight_distance(negative_inverse_density: f64) -> f64 {
+    negative_inverse_density * random::<f64>().ln()
+}
+
 #[derive(Clone)]
 pub struct ConstantMedium {
     boundary: Arc<dyn Hittable + Send + Sync >,
@@ -76,7 +81,7 @@ impl Hittable for ConstantMedium {
 
         let ray_length = ray.direction.length();
         let distance_inside_boundary = (hit_record_2.t - hit_record_1.t) * ray_length;
-        let hit_distance = self.negative_inverse_density * random::<f64>().ln();
+        let hit_distance = free_flight_distance(self.negative_inverse_density);
 
         if hit_distance > distance_inside_boundary {
             return false;
@@ -90,6 +95,8 @@ impl Hittable for ConstantMedium {
         // }
 
         hit_record.material = Material::Isotropic(self.phase_function.clone());
+        hit_record.light_group = 0;
+        hit_record.light_unlinked = false;
 
         true
     }
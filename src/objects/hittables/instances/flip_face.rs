@@ -0,0 +1,32 @@
+use std::sync::Arc;
+use crate::data_structs::ray::Ray;
+use crate::objects::aabb::AABB;
+use crate::objects::hittables::{HitRecord, Hittable};
+
+/// Swaps the front and back face of a hittable, e.g. to point a one-sided light downwards.
+#[derive(Clone)]
+pub struct FlipFace {
+    hittable: Arc<dyn Hittable + Send + Sync>,
+}
+
+impl FlipFace {
+    pub fn new<T: Hittable + Send + Sync + 'static>(hittable: T) -> Self {
+        Self { hittable: Arc::new(hittable) }
+    }
+}
+
+impl Hittable for FlipFace {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, hit_record: &mut HitRecord) -> bool {
+        if !self.hittable.hit(ray, t_min, t_max, hit_record) {
+            return false;
+        }
+
+        hit_record.front_face = !hit_record.front_face;
+
+        true
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut AABB) -> bool {
+        self.hittable.bounding_box(time0, time1, output_box)
+    }
+}
@@ -1,2 +1,4 @@
 pub mod translate;
-pub mod rotate_y;
\ No newline at end of file
+pub mod rotate_y;
+pub mod flip_face;
+pub mod visibility;
@@ -60,6 +60,14 @@ impl RotateY {
             bbox: AABB::new(minimum_point, maximum_point),
         }
     }
+
+    fn rotate_to_world(&self, vector: Vec3) -> Vec3 {
+        Vec3::new(
+            self.cos_theta * vector.x + self.sin_theta * vector.z,
+            vector.y,
+            -self.sin_theta * vector.x + self.cos_theta * vector.z,
+        )
+    }
 }
 
 impl Hittable for RotateY {
@@ -73,10 +81,11 @@ impl Hittable for RotateY {
         direction[0] = self.cos_theta * ray.direction.x - self.sin_theta * ray.direction.z;
         direction[2] = self.sin_theta * ray.direction.x + self.cos_theta * ray.direction.z;
 
-        let rotated_ray = Ray::new(
-            Point3::from_vector(origin),
-            Point3::from_vector(direction),
-            ray.time);
+        let rotated_ray = Ray {
+            origin: Point3::from_vector(origin),
+            direction: Point3::from_vector(direction),
+            ..*ray
+        };
 
         if !self.hittable.hit(&rotated_ray, t_min, t_max, hit_record) {
             return false;
@@ -92,6 +101,8 @@ impl Hittable for RotateY {
         normal[2] = -self.sin_theta * hit_record.normal.x + self.cos_theta * hit_record.normal.z;
 
         hit_record.point = Point3::from_vector(point);
+        hit_record.dpdu = self.rotate_to_world(hit_record.dpdu);
+        hit_record.dpdv = self.rotate_to_world(hit_record.dpdv);
         hit_record.set_face_normal(&rotated_ray, Vec3::from_vector(normal));
 
         true
@@ -18,7 +18,7 @@ impl Translate {
 
 impl Hittable for Translate {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, hit_record: &mut HitRecord) -> bool {
-        let moved_ray = Ray::new(ray.origin - self.offset, ray.direction, ray.time);
+        let moved_ray = Ray { origin: ray.origin - self.offset, ..*ray };
         if !self.hittable.hit(&moved_ray, t_min, t_max, hit_record) {
             return false;
         }
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use crate::data_structs::ray::{Ray, RayKind};
+use crate::lights::LightLinks;
+use crate::objects::aabb::AABB;
+use crate::objects::hittables::{HitRecord, Hittable};
+
+/// Which kinds of rays can hit an object.
+#[derive(Copy, Clone)]
+pub struct VisibilityFlags {
+    pub camera: bool,
+    pub shadow: bool,
+    pub diffuse: bool,
+    pub specular: bool,
+    pub volume: bool,
+}
+
+impl VisibilityFlags {
+    pub const ALL: VisibilityFlags = VisibilityFlags {
+        camera: true,
+        shadow: true,
+        diffuse: true,
+        specular: true,
+        volume: true,
+    };
+
+    pub fn is_visible_to(&self, kind: RayKind) -> bool {
+        match kind {
+            RayKind::Camera => self.camera,
+            RayKind::Shadow => self.shadow,
+            RayKind::Diffuse => self.diffuse,
+            RayKind::Specular => self.specular,
+            RayKind::Volume => self.volume,
+        }
+    }
+}
+
+impl Default for VisibilityFlags {
+    fn default() -> Self {
+        VisibilityFlags::ALL
+    }
+}
+
+/// Hides a hittable from some kinds of rays and assigns it a light linking group.
+/// E.g. an object invisible to the camera that still casts shadows and shows up in reflections.
+/// Emissive objects can also be given light links, limiting which groups their emission reaches.
+#[derive(Clone)]
+pub struct Visibility {
+    hittable: Arc<dyn Hittable + Send + Sync>,
+    flags: VisibilityFlags,
+    light_group: Option<u32>,
+    light_links: LightLinks,
+}
+
+impl Visibility {
+    pub fn new<T: Hittable + Send + Sync + 'static>(hittable: T, flags: VisibilityFlags) -> Self {
+        Self { hittable: Arc::new(hittable), flags, light_group: None, light_links: LightLinks::All }
+    }
+
+    /// Puts the object in a group that lights can include or exclude. The innermost group wins.
+    pub fn with_light_group(self, light_group: u32) -> Self {
+        Self { light_group: Some(light_group), ..self }
+    }
+
+    /// Light linking groups the emission of the object reaches. The camera always sees it.
+    pub fn with_light_links(self, light_links: LightLinks) -> Self {
+        Self { light_links, ..self }
+    }
+}
+
+impl Hittable for Visibility {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, hit_record: &mut HitRecord) -> bool {
+        if !self.flags.is_visible_to(ray.kind) || !self.hittable.hit(ray, t_min, t_max, hit_record) {
+            return false;
+        }
+
+        if let Some(light_group) = self.light_group {
+            if hit_record.light_group == 0 {
+                hit_record.light_group = light_group;
+            }
+        }
+
+        if ray.kind != RayKind::Camera && !self.light_links.illuminates(ray.light_group) {
+            hit_record.light_unlinked = true;
+        }
+
+        true
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut AABB) -> bool {
+        self.hittable.bounding_box(time0, time1, output_box)
+    }
+}
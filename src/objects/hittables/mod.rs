@@ -1,5 +1,5 @@
 use crate::data_structs::ray::Ray;
-use crate::data_structs::vec3::{Color, Point3, Vec3};
+use crate::data_structs::vec3::{Point3, Vec3};
 use crate::materials::Material;
 use crate::objects::aabb::AABB;
 use std::sync::Arc;
@@ -12,17 +12,26 @@ pub mod sphere;
 pub mod rectangles;
 pub mod instances;
 pub mod constant_medium;
+pub mod subsurface_medium;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct HitRecord {
     pub point: Point3,
     pub normal: Vec3,
+    /// Partial derivatives of the surface point with respect to the texture coordinates.
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
     pub t: f64,
     pub u: f64,
     pub v: f64,
     pub front_face: bool,
     pub material: Material,
-    pub background: Color
+    /// Light linking group of the object that was hit. Primitives reset it to zero, the default group,
+    /// and `Visibility` instances overwrite it.
+    pub light_group: u32,
+    /// Set by `Visibility` instances whose light links leave out the light group of the ray that hit
+    /// them, so their emission does not count. Primitives reset it.
+    pub light_unlinked: bool,
 }
 
 impl HitRecord {
@@ -38,8 +47,26 @@ impl HitRecord {
         self.front_face = front_face;
     }
 
-    pub fn emitted(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
-        self.background
+    /// Unit tangent along `dpdu`, made orthogonal to the shading normal.
+    pub fn tangent(&self) -> Vec3 {
+        let tangent = self.dpdu - self.normal.dot(self.dpdu) * self.normal;
+        if !tangent.near_zero() {
+            return tangent.normalize();
+        }
+
+        // Primitives without a parameterization get an arbitrary tangent.
+        let helper = if self.normal.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+
+        helper.cross(&self.normal).normalize()
+    }
+
+    /// Unit bitangent completing the right-handed frame `(tangent, bitangent, normal)`.
+    pub fn bitangent(&self) -> Vec3 {
+        self.normal.cross(&self.tangent())
     }
 }
 
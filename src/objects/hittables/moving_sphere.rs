@@ -1,9 +1,9 @@
 use crate::data_structs::ray::Ray;
 use crate::data_structs::vec3::{Point3, Vec3};
-use crate::materials::Material;
+use crate::materials::{Maskable, Material};
 use crate::objects::aabb::AABB;
 use crate::objects::hittables::{HitRecord, Hittable};
-use crate::objects::hittables::sphere::root_check;
+use crate::objects::hittables::sphere::{root_check, Sphere};
 
 #[derive(Clone)]
 pub struct MovingSphere {
@@ -47,22 +47,34 @@ impl Hittable for MovingSphere {
 
         let discriminant_root = discriminant.sqrt();
 
-        let mut root = (-half_b - discriminant_root) / a;
-        if root_check(root, t_min, t_max) {
-            root = (-half_b + discriminant_root) / a;
+        for root in [(-half_b - discriminant_root) / a, (-half_b + discriminant_root) / a] {
             if root_check(root, t_min, t_max) {
-                return false;
+                continue;
             }
-        }
 
-        hit_record.t = root;
-        hit_record.point = ray.at(root);
+            let point = ray.at(root);
+            let outward_normal = (point - self.center(ray.time)) / self.radius;
 
-        let outward_normal = (hit_record.point - self.center(ray.time)) / self.radius;
-        hit_record.set_face_normal(&ray, outward_normal);
-        hit_record.material = self.material.clone();
+            let (mut u, mut v) = (0.0, 0.0);
+            Sphere::get_sphere_uv(&outward_normal, &mut u, &mut v);
+            if !self.material.is_opaque(u, v, &point) {
+                continue;
+            }
 
-        true
+            hit_record.t = root;
+            hit_record.point = point;
+            hit_record.set_face_normal(ray, outward_normal);
+            hit_record.u = u;
+            hit_record.v = v;
+            Sphere::get_sphere_tangents(&outward_normal, self.radius, &mut hit_record.dpdu, &mut hit_record.dpdv);
+            hit_record.material = self.material.clone();
+            hit_record.light_group = 0;
+            hit_record.light_unlinked = false;
+
+            return true;
+        }
+
+        false
     }
 
     fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut AABB) -> bool {
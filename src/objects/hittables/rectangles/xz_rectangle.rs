@@ -1,6 +1,6 @@
 use crate::data_structs::ray::Ray;
 use crate::data_structs::vec3::{Point3, Vec3};
-use crate::materials::Material;
+use crate::materials::{Maskable, Material};
 use crate::objects::aabb::AABB;
 use crate::objects::hittables::{HitRecord, Hittable};
 
@@ -33,12 +33,23 @@ impl Hittable for XzRectangle {
             return false;
         }
 
-        hit_record.u = (x - self.x0) / (self.x1 - self.x0);
-        hit_record.v = (z - self.z0) / (self.z1 - self.z0);
+        let u = (x - self.x0) / (self.x1 - self.x0);
+        let v = (z - self.z0) / (self.z1 - self.z0);
+        let point = ray.at(t);
+        if !self.material.is_opaque(u, v, &point) {
+            return false;
+        }
+
+        hit_record.u = u;
+        hit_record.v = v;
+        hit_record.dpdu = Vec3::new(self.x1 - self.x0, 0.0, 0.0);
+        hit_record.dpdv = Vec3::new(0.0, 0.0, self.z1 - self.z0);
         hit_record.t = t;
         hit_record.set_face_normal(ray, Vec3::new(0.0, 1.0, 0.0));
         hit_record.material = self.material.clone();
-        hit_record.point = ray.at(t);
+        hit_record.light_group = 0;
+        hit_record.light_unlinked = false;
+        hit_record.point = point;
 
         true
     }
@@ -1,7 +1,7 @@
 use std::f64::consts::PI;
 use crate::data_structs::ray::Ray;
 use crate::data_structs::vec3::{Point3, Vec3};
-use crate::materials::Material;
+use crate::materials::{Maskable, Material};
 use crate::objects::aabb::AABB;
 use crate::objects::hittables::{HitRecord, Hittable};
 
@@ -28,6 +28,24 @@ impl Sphere {
         *u = phi / (2.0 * PI);
         *v = theta / PI;
     }
+
+    /// Surface derivatives for the parameterization used by `get_sphere_uv`.
+    pub fn get_sphere_tangents(point: &Point3, radius: f64, dpdu: &mut Vec3, dpdv: &mut Vec3) {
+        let sin_theta = (point.x * point.x + point.z * point.z).sqrt();
+
+        *dpdu = 2.0 * PI * radius * Vec3::new(point.z, 0.0, -point.x);
+        *dpdv = if sin_theta > 1e-8 {
+            PI * radius * Vec3::new(
+                -point.x * point.y / sin_theta,
+                sin_theta,
+                -point.y * point.z / sin_theta,
+            )
+        } else {
+            // At the poles u is degenerate; pick any direction in the tangent plane.
+            *dpdu = 2.0 * PI * radius * Vec3::new(0.0, 0.0, -1.0);
+            PI * radius * Vec3::new(1.0, 0.0, 0.0)
+        };
+    }
 }
 
 pub fn root_check(root: f64, t_min: f64, t_max: f64) -> bool {
@@ -50,26 +68,38 @@ impl Hittable for Sphere {
 
         let discriminant_root = discriminant.sqrt();
 
-        let mut root = (-half_b - discriminant_root) / a;
-        if root_check(root, t_min, t_max) {
-            root = (-half_b + discriminant_root) / a;
+        // Try the nearest root first and fall back to the far one if it is out of range or cut out.
+        for root in [(-half_b - discriminant_root) / a, (-half_b + discriminant_root) / a] {
             if root_check(root, t_min, t_max) {
-                return false;
+                continue;
             }
-        }
 
-        hit_record.t = root;
-        hit_record.point = ray.at(root);
+            let point = ray.at(root);
+            let outward_normal = (point - self.center) / self.radius;
+
+            let (mut u, mut v) = (0.0, 0.0);
+            Sphere::get_sphere_uv(&outward_normal, &mut u, &mut v);
+            if !self.material.is_opaque(u, v, &point) {
+                continue;
+            }
 
-        let outward_normal = (hit_record.point - self.center) / self.radius;
-        hit_record.set_face_normal(&ray, outward_normal);
+            hit_record.t = root;
+            hit_record.point = point;
+            hit_record.set_face_normal(ray, outward_normal);
 
-        // Update to correct u and v coordinates.
-        Sphere::get_sphere_uv(&outward_normal, &mut hit_record.u, &mut hit_record.v);
+            // Update to correct u and v coordinates.
+            hit_record.u = u;
+            hit_record.v = v;
+            Sphere::get_sphere_tangents(&outward_normal, self.radius, &mut hit_record.dpdu, &mut hit_record.dpdv);
 
-        hit_record.material = self.material.clone();
+            hit_record.material = self.material.clone();
+            hit_record.light_group = 0;
+            hit_record.light_unlinked = false;
 
-        true
+            return true;
+        }
+
+        false
     }
 
     fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut AABB) -> bool {
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+use crate::data_structs::random::random;
+use crate::data_structs::ray::Ray;
+use crate::data_structs::vec3::Color;
+use crate::materials::Material;
+use crate::materials::subsurface::{Subsurface, SubsurfaceEvent};
+use crate::objects::aabb::AABB;
+use crate::objects::hittables::{HitRecord, Hittable};
+use crate::objects::hittables::constant_medium::free_flight_distance;
+
+/// Closed boundary filled with a subsurface scattering material.
+/// Rays refract in at the boundary and random walk inside until they leave again.
+#[derive(Clone)]
+pub struct SubsurfaceMedium {
+    boundary: Arc<dyn Hittable + Send + Sync>,
+    material: Subsurface,
+}
+
+impl SubsurfaceMedium {
+    pub fn new<H: Hittable + Send + Sync + 'static>(hittable: H, material: Subsurface) -> Self {
+        Self { boundary: Arc::new(hittable), material }
+    }
+}
+
+fn average(color: Color) -> f64 {
+    (color.x + color.y + color.z) / 3.0
+}
+
+impl Hittable for SubsurfaceMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, hit_record: &mut HitRecord) -> bool {
+        let mut boundary_record = HitRecord::default();
+        if !self.boundary.hit(ray, t_min, t_max, &mut boundary_record) {
+            return false;
+        }
+
+        // Coming from the outside. The boundary refracts the ray into the volume.
+        if boundary_record.front_face {
+            *hit_record = boundary_record;
+            hit_record.material = Material::Subsurface(self.material.with_event(SubsurfaceEvent::Entry));
+
+            return true;
+        }
+
+        // Inside the volume. Sample the free flight distance with one randomly picked color channel.
+        let density = self.material.density();
+        let channel_density = match (random::<f64>() * 3.0) as usize {
+            0 => density.x,
+            1 => density.y,
+            _ => density.z,
+        };
+
+        let ray_length = ray.direction.length();
+        let distance_to_boundary = boundary_record.t * ray_length;
+        let hit_distance = free_flight_distance(-1.0 / channel_density);
+
+        let transmittance = |distance: f64| Color::new(
+            (-density.x * distance).exp(),
+            (-density.y * distance).exp(),
+            (-density.z * distance).exp(),
+        );
+
+        if hit_distance < distance_to_boundary {
+            let scatter_transmittance = density * transmittance(hit_distance);
+            let weight = scatter_transmittance / average(scatter_transmittance);
+
+            *hit_record = boundary_record;
+            hit_record.t = (hit_distance / ray_length).max(t_min);
+            hit_record.point = ray.at(hit_record.t);
+            hit_record.material = Material::Subsurface(self.material.with_event(SubsurfaceEvent::Scatter(weight)));
+        } else {
+            let exit_transmittance = transmittance(distance_to_boundary);
+            let weight = exit_transmittance / average(exit_transmittance);
+
+            *hit_record = boundary_record;
+            hit_record.material = Material::Subsurface(self.material.with_event(SubsurfaceEvent::Exit(weight)));
+        }
+
+        true
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut AABB) -> bool {
+        self.boundary.bounding_box(time0, time1, output_box)
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt;
+use std::fs;
+use rand::Rng;
+use crate::data_structs::random::rng;
+use crate::data_structs::ray::Ray;
+use crate::data_structs::vec3::{Point3, Vec3};
+use crate::objects::camera::Camera;
+
+/// One spherical interface of a lens prescription. Lengths are in millimeters.
+#[derive(Copy, Clone)]
+pub struct LensElement {
+    /// Radius of curvature, positive when the center lies towards the film. Zero marks the aperture stop.
+    pub radius: f64,
+    /// Distance to the next interface towards the film.
+    pub thickness: f64,
+    /// Index of refraction between this interface and the next. Zero or one is air.
+    pub ior: f64,
+    /// Diameter of the clear aperture.
+    pub aperture: f64,
+}
+
+impl LensElement {
+    /// Reads a prescription table with the columns radius, thickness, ior and aperture, one interface per
+    /// line from the front of the lens to the back. Lines starting with `#` are comments.
+    pub fn from_file(filename: &str) -> Vec<LensElement> {
+        let contents = fs::read_to_string(filename).expect("Lens prescription file not found.");
+
+        contents
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let columns = line
+                    .split_whitespace()
+                    .map(|column| column.parse::<f64>().expect("Lens prescription has an invalid number."))
+                    .collect::<Vec<f64>>();
+                assert!(columns.len() >= 4, "Lens prescription lines need four columns.");
+
+                LensElement { radius: columns[0], thickness: columns[1], ior: columns[2], aperture: columns[3] }
+            })
+            .collect()
+    }
+
+    fn medium_ior(&self) -> f64 {
+        if self.ior == 0.0 { 1.0 } else { self.ior }
+    }
+}
+
+/// Why a lens prescription cannot be focused.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LensError {
+    /// The prescription has no elements.
+    Empty,
+    /// Rays along the axis do not make it through the lens.
+    Blocked,
+    /// The focus distance in meters is closer than the lens can focus.
+    TooClose(f64),
+}
+
+impl fmt::Display for LensError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LensError::Empty => write!(f, "Lens prescription has no elements."),
+            LensError::Blocked => write!(f, "Lens prescription blocks rays along its axis, so it cannot be focused."),
+            LensError::TooClose(distance) => write!(f, "Lens cannot focus at {distance} m, closer than its minimum focus distance."),
+        }
+    }
+}
+
+impl std::error::Error for LensError {}
+
+/// Camera tracing rays from the film through every element of a real lens. Distortion, vignetting and
+/// focus breathing come out of the lens design.
+///
+/// Lens space has the film at `z = 0` and the lens in front of it towards positive `z`, in millimeters.
+/// Scene units are taken to be meters.
+#[derive(Clone)]
+pub struct LensCamera {
+    elements: Vec<LensElement>,
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    film_width: f64,
+    aspect_ratio: f64,
+    focus_distance: f64,
+    start_time: f64,
+    end_time: f64,
+}
+
+impl LensCamera {
+    /// Full frame film focused on `look_at`.
+    pub fn new(look_from: Point3, look_at: Point3, up_vector: Vec3, aspect_ratio: f64, elements: Vec<LensElement>) -> Result<Self, LensError> {
+        if elements.is_empty() {
+            return Err(LensError::Empty);
+        }
+
+        let w = (look_from - look_at).normalize();
+        let u = up_vector.cross(&w).normalize();
+        let v = w.cross(&u);
+
+        Self {
+            elements,
+            origin: look_from,
+            u,
+            v,
+            w,
+            film_width: 36.0,
+            aspect_ratio,
+            focus_distance: (look_at - look_from).length(),
+            start_time: 0.0,
+            end_time: 1.0,
+        }.focused()
+    }
+
+    /// Film width in millimeters. The height follows from the aspect ratio.
+    pub fn with_film_width(self, film_width: f64) -> Self {
+        Self { film_width, ..self }
+    }
+
+    /// Moves the lens so the plane `focus_distance` meters away is sharp.
+    pub fn with_focus_distance(self, focus_distance: f64) -> Result<Self, LensError> {
+        Self { focus_distance, ..self }.focused()
+    }
+
+    pub fn with_shutter(self, start_time: f64, end_time: f64) -> Self {
+        Self { start_time, end_time, ..self }
+    }
+
+    /// Sets the distance between the rear element and the film from a thick lens approximation.
+    fn focused(mut self) -> Result<Self, LensError> {
+        let last = self.elements.len() - 1;
+        self.elements[last].thickness = 0.0;
+
+        // Principal planes and focal points on the film side and the scene side, from rays parallel to the axis.
+        let height = 0.001 * self.film_width;
+        let front_z = self.front_z();
+        let from_scene = Ray::new(Point3::new(height, 0.0, front_z + 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let from_film = Ray::new(Point3::new(height, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+
+        let (film_principal, film_focal) = Self::cardinal_points(&from_scene, &self.trace(&from_scene, false).ok_or(LensError::Blocked)?);
+        let (scene_principal, _) = Self::cardinal_points(&from_film, &self.trace(&from_film, true).ok_or(LensError::Blocked)?);
+
+        let focal_length = film_principal - film_focal;
+        let z = self.focus_distance * 1000.0;
+        let c = (z - scene_principal + film_principal) * (z - scene_principal - 4.0 * focal_length + film_principal);
+        let film_distance = 0.5 * (z - scene_principal - film_principal - c.sqrt());
+        if c < 0.0 || film_distance <= 0.0 || film_distance.is_nan() {
+            return Err(LensError::TooClose(self.focus_distance));
+        }
+        self.elements[last].thickness = film_distance;
+
+        Ok(self)
+    }
+
+    /// Where a ray parallel to the axis crosses its original height and the axis after going through the lens.
+    fn cardinal_points(entering: &Ray, leaving: &Ray) -> (f64, f64) {
+        let focal = -leaving.origin.x / leaving.direction.x;
+        let principal = (entering.origin.x - leaving.origin.x) / leaving.direction.x;
+
+        (leaving.at(principal).z, leaving.at(focal).z)
+    }
+
+    fn front_z(&self) -> f64 {
+        self.elements.iter().map(|element| element.thickness).sum()
+    }
+
+    fn film_height(&self) -> f64 {
+        self.film_width / self.aspect_ratio
+    }
+
+    /// Refracts a ray through every interface. Rays from the film go front to back through the list,
+    /// rays from the scene back to front. Returns `None` when an element blocks the ray.
+    fn trace(&self, ray: &Ray, from_film: bool) -> Option<Ray> {
+        let mut ray = *ray;
+
+        // Vertex positions along the axis, front element first.
+        let mut positions = Vec::with_capacity(self.elements.len());
+        let mut z = self.front_z();
+        for element in self.elements.iter() {
+            positions.push(z);
+            z -= element.thickness;
+        }
+
+        let order = (0..self.elements.len()).collect::<Vec<usize>>();
+        let order = if from_film { order.into_iter().rev().collect::<Vec<usize>>() } else { order };
+
+        for i in order {
+            let element = &self.elements[i];
+            let vertex = positions[i];
+
+            // Medium before and after the interface, as seen along the ray.
+            let behind = element.medium_ior();
+            let in_front = if i > 0 { self.elements[i - 1].medium_ior() } else { 1.0 };
+            let (ior_in, ior_out) = if from_film { (behind, in_front) } else { (in_front, behind) };
+
+            let (t, normal) = if element.radius == 0.0 {
+                ((vertex - ray.origin.z) / ray.direction.z, Vec3::new(0.0, 0.0, 1.0))
+            } else {
+                let center = Point3::new(0.0, 0.0, vertex - element.radius);
+                Self::intersect_sphere(&ray, &center, element.radius)?
+            };
+            if t <= 0.0 {
+                return None;
+            }
+
+            let point = ray.at(t);
+            if point.x * point.x + point.y * point.y > element.aperture * element.aperture / 4.0 {
+                return None;
+            }
+
+            let mut direction = ray.direction;
+            if element.radius != 0.0 {
+                let unit_direction = direction.normalize();
+                let normal = if normal.dot(unit_direction) > 0.0 { -normal } else { normal };
+                let cos_theta = (-unit_direction.dot(normal)).min(1.0);
+                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+                if ior_in / ior_out * sin_theta > 1.0 {
+                    return None;
+                }
+                direction = Vec3::refract(unit_direction, normal, ior_in / ior_out);
+            }
+
+            ray = Ray { origin: point, direction, ..ray };
+        }
+
+        Some(ray)
+    }
+
+    /// Parameter of the hit on the side of the sphere that faces the lens vertex, and the outward normal there.
+    fn intersect_sphere(ray: &Ray, center: &Point3, radius: f64) -> Option<(f64, Vec3)> {
+        let oc = ray.origin - *center;
+        let a = ray.direction.length_squared();
+        let half_b = oc.dot(ray.direction);
+        let c = oc.length_squared() - radius * radius;
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        let (t0, t1) = ((-half_b - root) / a, (-half_b + root) / a);
+        let use_closer = (ray.direction.z < 0.0) != (radius < 0.0);
+        let t = if use_closer { t0 } else { t1 };
+
+        Some((t, (ray.at(t) - *center).normalize()))
+    }
+}
+
+impl Camera for LensCamera {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let mut rng = rng();
+
+        // The lens flips the image, so the film is read upside down and mirrored.
+        let film_point = Point3::new((0.5 - s) * self.film_width, (0.5 - t) * self.film_height(), 0.0);
+
+        // Aim at a random point on the rear element.
+        let rear = self.elements[self.elements.len() - 1];
+        let disk = rear.aperture / 2.0 * Vec3::random_in_unit_disk();
+        let target = Point3::new(disk.x, disk.y, rear.thickness);
+
+        let time = rng.gen_range(self.start_time..self.end_time);
+        let ray = self.trace(&Ray::new(film_point, target - film_point, time), true)?;
+
+        let to_world = |vector: Vec3| vector.x * self.u + vector.y * self.v - vector.z * self.w;
+
+        Some(Ray::new(self.origin + to_world(ray.origin) / 1000.0, to_world(ray.direction), time))
+    }
+}
@@ -1,3 +1,6 @@
 pub mod aabb;
 pub mod camera;
-pub mod hittables;
\ No newline at end of file
+pub mod hittables;
+pub mod physical_camera;
+pub mod aperture;
+pub mod lens_camera;
@@ -0,0 +1,142 @@
+use crate::data_structs::ray::Ray;
+use crate::data_structs::vec3::{Point3, Vec3};
+use crate::objects::aperture::Aperture;
+use crate::objects::camera::{Camera, ProjectionCamera};
+
+/// Perspective camera set up in photographic units. Scene units are taken to be meters.
+///
+/// The field of view follows from the sensor size and focal length, depth of field from the f-stop
+/// and focus distance, and motion blur from the shutter speed. Exposure is relative to the
+/// "sunny 16" settings (f/16, 1/100 s, ISO 100), which leave the image as it is.
+#[derive(Clone)]
+pub struct PhysicalCamera {
+    settings: Settings,
+    camera: ProjectionCamera,
+}
+
+#[derive(Clone)]
+struct Settings {
+    look_from: Point3,
+    look_at: Point3,
+    up_vector: Vec3,
+    aspect_ratio: f64,
+    sensor_width: f64,
+    sensor_height: f64,
+    focal_length: f64,
+    f_stop: f64,
+    focus_distance: f64,
+    shutter_open: f64,
+    shutter_speed: f64,
+    iso: f64,
+    aperture_shape: Aperture,
+}
+
+impl Settings {
+    fn fov(&self) -> f64 {
+        let sensor_aspect_ratio = self.sensor_width / self.sensor_height;
+        let film_height = if self.aspect_ratio >= sensor_aspect_ratio {
+            self.sensor_width / self.aspect_ratio
+        } else {
+            self.sensor_height
+        };
+
+        2.0 * (film_height / (2.0 * self.focal_length)).atan().to_degrees()
+    }
+
+    fn aperture(&self) -> f64 {
+        self.focal_length / self.f_stop / 1000.0
+    }
+}
+
+impl PhysicalCamera {
+    /// Full frame 50 mm lens at f/8, 1/125 s and ISO 100, focused on `look_at`.
+    pub fn new(look_from: Point3, look_at: Point3, up_vector: Vec3, aspect_ratio: f64) -> Self {
+        Self::from_settings(Settings {
+            look_from,
+            look_at,
+            up_vector,
+            aspect_ratio,
+            sensor_width: 36.0,
+            sensor_height: 24.0,
+            focal_length: 50.0,
+            f_stop: 8.0,
+            focus_distance: (look_at - look_from).length(),
+            shutter_open: 0.0,
+            shutter_speed: 1.0 / 125.0,
+            iso: 100.0,
+            aperture_shape: Aperture::circle(),
+        })
+    }
+
+    fn from_settings(settings: Settings) -> Self {
+        let camera = ProjectionCamera::new(
+            settings.look_from,
+            settings.look_at,
+            settings.up_vector,
+            settings.fov(),
+            settings.aspect_ratio,
+            settings.aperture(),
+            settings.focus_distance,
+            settings.shutter_open,
+            settings.shutter_open + settings.shutter_speed,
+        ).with_aperture(settings.aperture_shape.clone());
+
+        Self { settings, camera }
+    }
+
+    /// Sensor size in millimeters. The image is fit inside the sensor.
+    pub fn with_sensor(self, width: f64, height: f64) -> Self {
+        Self::from_settings(Settings { sensor_width: width, sensor_height: height, ..self.settings })
+    }
+
+    /// Focal length in millimeters.
+    pub fn with_focal_length(self, focal_length: f64) -> Self {
+        Self::from_settings(Settings { focal_length, ..self.settings })
+    }
+
+    pub fn with_f_stop(self, f_stop: f64) -> Self {
+        Self::from_settings(Settings { f_stop, ..self.settings })
+    }
+
+    /// Distance to the plane in focus, in meters.
+    pub fn with_focus_distance(self, focus_distance: f64) -> Self {
+        Self::from_settings(Settings { focus_distance, ..self.settings })
+    }
+
+    /// The shutter opens at scene time `open` and stays open for `speed` seconds.
+    pub fn with_shutter(self, open: f64, speed: f64) -> Self {
+        Self::from_settings(Settings { shutter_open: open, shutter_speed: speed, ..self.settings })
+    }
+
+    pub fn with_iso(self, iso: f64) -> Self {
+        Self::from_settings(Settings { iso, ..self.settings })
+    }
+
+    /// Shape of the aperture blades or a custom aperture.
+    pub fn with_aperture_shape(self, aperture_shape: Aperture) -> Self {
+        Self::from_settings(Settings { aperture_shape, ..self.settings })
+    }
+
+    /// Vertical field of view in degrees.
+    pub fn fov(&self) -> f64 {
+        self.settings.fov()
+    }
+
+    /// Diameter of the entrance pupil in meters.
+    pub fn aperture(&self) -> f64 {
+        self.settings.aperture()
+    }
+}
+
+impl Camera for PhysicalCamera {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        self.camera.get_ray(s, t)
+    }
+
+    fn exposure(&self) -> f64 {
+        let settings = &self.settings;
+        let sunny_sixteen = (1.0 / 100.0) * 100.0 / (16.0 * 16.0);
+
+        settings.shutter_speed * settings.iso / (settings.f_stop * settings.f_stop) / sunny_sixteen * self.camera.exposure()
+    }
+}
@@ -0,0 +1,265 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, Write};
+
+use crate::data_structs::vec3::Color;
+use crate::render::framebuffer::{Framebuffer, Pixel};
+
+const MAGIC: &[u8; 4] = b"RTCK";
+const VERSION: u32 = 3;
+/// Sum, squared luminance and sample count of a pixel.
+const PIXEL_BYTES: u64 = 4 * 8 + 4;
+
+/// What a checkpoint has to match to be resumed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RenderSettings {
+    pub scene: String,
+    pub scene_seed: u64,
+    pub width: u32,
+    pub height: u32,
+    pub max_depth: u32,
+    pub render_mode: String,
+    pub samples_per_pixel: u32,
+    pub min_samples_per_pixel: u32,
+    pub noise_threshold: f64,
+    pub tile_size: u32,
+    pub tile_order: String,
+}
+
+/// Raw state of an unfinished render: the linear sample sums and counts of every pixel, how many passes
+/// are done and the seed every sample derives its random numbers from.
+pub struct Checkpoint {
+    pub settings: RenderSettings,
+    pub seed: u64,
+    pub passes: u32,
+    pub framebuffer: Framebuffer,
+}
+
+impl Checkpoint {
+    /// A render that has not started yet.
+    pub fn new(settings: RenderSettings, seed: u64) -> Self {
+        let framebuffer = Framebuffer::new(settings.width, settings.height);
+
+        Checkpoint { settings, seed, passes: 0, framebuffer }
+    }
+
+    /// Writes next to `path` first and renames over it, so a crash while saving keeps the previous checkpoint.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let temporary_path = format!("{path}.tmp");
+        let mut writer = BufWriter::new(File::create(&temporary_path)?);
+
+        writer.write_all(MAGIC)?;
+        write_u32(&mut writer, VERSION)?;
+        write_string(&mut writer, &self.settings.scene)?;
+        writer.write_all(&self.settings.scene_seed.to_le_bytes())?;
+        write_u32(&mut writer, self.settings.width)?;
+        write_u32(&mut writer, self.settings.height)?;
+        write_u32(&mut writer, self.settings.max_depth)?;
+        write_string(&mut writer, &self.settings.render_mode)?;
+        write_u32(&mut writer, self.settings.samples_per_pixel)?;
+        write_u32(&mut writer, self.settings.min_samples_per_pixel)?;
+        writer.write_all(&self.settings.noise_threshold.to_le_bytes())?;
+        write_u32(&mut writer, self.settings.tile_size)?;
+        write_string(&mut writer, &self.settings.tile_order)?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        write_u32(&mut writer, self.passes)?;
+
+        for pixel in self.framebuffer.pixels.iter() {
+            for value in [pixel.sum.x, pixel.sum.y, pixel.sum.z, pixel.luminance_squares] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            write_u32(&mut writer, pixel.samples)?;
+        }
+
+        writer.into_inner()?.sync_all()?;
+        fs::rename(temporary_path, path)
+    }
+
+    pub fn load(path: &str) -> io::Result<Checkpoint> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(&mut reader)? != VERSION {
+            return Err(invalid_data("Not a checkpoint of this renderer version."));
+        }
+
+        let settings = RenderSettings {
+            scene: read_string(&mut reader)?,
+            scene_seed: read_u64(&mut reader)?,
+            width: read_u32(&mut reader)?,
+            height: read_u32(&mut reader)?,
+            max_depth: read_u32(&mut reader)?,
+            render_mode: read_string(&mut reader)?,
+            samples_per_pixel: read_u32(&mut reader)?,
+            min_samples_per_pixel: read_u32(&mut reader)?,
+            noise_threshold: read_f64(&mut reader)?,
+            tile_size: read_u32(&mut reader)?,
+            tile_order: read_string(&mut reader)?,
+        };
+        let seed = read_u64(&mut reader)?;
+        let passes = read_u32(&mut reader)?;
+
+        // Check the size against the file before allocating, so a damaged header cannot ask for any amount of memory.
+        let pixel_count = Framebuffer::pixel_count(settings.width, settings.height)
+            .filter(|&count| count > 0)
+            .ok_or_else(|| invalid_data("Checkpoint has an invalid image size."))?;
+        let remaining = reader.get_ref().metadata()?.len().saturating_sub(reader.stream_position()?);
+        if (pixel_count as u64).checked_mul(PIXEL_BYTES) != Some(remaining) {
+            return Err(invalid_data("Checkpoint size does not match its image size."));
+        }
+
+        let mut framebuffer = Framebuffer::new(settings.width, settings.height);
+        for pixel in framebuffer.pixels.iter_mut() {
+            let sum = Color::new(read_f64(&mut reader)?, read_f64(&mut reader)?, read_f64(&mut reader)?);
+            let luminance_squares = read_f64(&mut reader)?;
+
+            *pixel = Pixel { sum, luminance_squares, samples: read_u32(&mut reader)? };
+        }
+
+        Ok(Checkpoint { settings, seed, passes, framebuffer })
+    }
+
+    /// Loads a checkpoint, rejecting it unless it was made with the same `settings`.
+    pub fn resume(path: &str, settings: &RenderSettings) -> io::Result<Checkpoint> {
+        let checkpoint = Checkpoint::load(path)?;
+
+        if &checkpoint.settings != settings {
+            return Err(invalid_data(&format!(
+                "Checkpoint was made with {:?}, not {:?}.",
+                checkpoint.settings, settings
+            )));
+        }
+
+        Ok(checkpoint)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    write_u32(writer, value.len() as u32)?;
+    writer.write_all(value.as_bytes())
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f64(reader: &mut impl Read) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let length = read_u32(reader)? as u64;
+    let mut bytes = Vec::new();
+    reader.take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(bytes).map_err(|_| invalid_data("Checkpoint has an invalid string."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(width: u32, height: u32) -> RenderSettings {
+        RenderSettings {
+            scene: "FinalScene".to_string(),
+            scene_seed: 7,
+            width,
+            height,
+            max_depth: 50,
+            render_mode: "Rgb".to_string(),
+            samples_per_pixel: 64,
+            min_samples_per_pixel: 16,
+            noise_threshold: 0.01,
+            tile_size: 32,
+            tile_order: "Spiral".to_string(),
+        }
+    }
+
+    fn temporary_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("rust_raytracer_{}_{name}.checkpoint", std::process::id())).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temporary_path("round_trip");
+        let mut checkpoint = Checkpoint::new(settings(3, 2), 42);
+        checkpoint.passes = 5;
+        for (index, pixel) in checkpoint.framebuffer.pixels.iter_mut().enumerate() {
+            pixel.add(Color::new(index as f64, 0.5, 2.0));
+            pixel.add(Color::new(1.0, index as f64, 0.25));
+        }
+
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::resume(&path, &checkpoint.settings).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.seed, 42);
+        assert_eq!(loaded.passes, 5);
+        for (saved, loaded) in checkpoint.framebuffer.pixels.iter().zip(loaded.framebuffer.pixels.iter()) {
+            assert_eq!((saved.sum.x, saved.sum.y, saved.sum.z), (loaded.sum.x, loaded.sum.y, loaded.sum.z));
+            assert_eq!(saved.luminance_squares, loaded.luminance_squares);
+            assert_eq!(saved.samples, loaded.samples);
+        }
+    }
+
+    #[test]
+    fn rejects_other_settings() {
+        let path = temporary_path("other_settings");
+        Checkpoint::new(settings(3, 2), 42).save(&path).unwrap();
+
+        let error = Checkpoint::resume(&path, &RenderSettings { scene_seed: 8, ..settings(3, 2) }).err().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        let path = temporary_path("truncated");
+        Checkpoint::new(settings(3, 2), 42).save(&path).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        let error = Checkpoint::load(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_size_beyond_file() {
+        // A header claiming a huge image has to fail before the framebuffer is allocated.
+        let path = temporary_path("huge");
+        let checkpoint = Checkpoint::new(settings(3, 2), 42);
+        checkpoint.save(&path).unwrap();
+
+        let mut bytes = fs::read(&path).unwrap();
+        let width_offset = MAGIC.len() + 4 + 4 + checkpoint.settings.scene.len() + 8;
+        bytes[width_offset..width_offset + 8].copy_from_slice(&[0xff; 8]);
+        fs::write(&path, bytes).unwrap();
+        let error = Checkpoint::load(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
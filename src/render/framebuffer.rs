@@ -0,0 +1,90 @@
+use image::{Rgb, RgbImage};
+
+use crate::data_structs::vec3::Color;
+
+/// Running sums over the samples of one pixel.
+#[derive(Copy, Clone, Default)]
+pub struct Pixel {
+    pub sum: Color,
+    pub luminance_squares: f64,
+    pub samples: u32,
+}
+
+impl Pixel {
+    pub fn add(&mut self, color: Color) {
+        self.sum += color;
+        self.luminance_squares += color.luminance() * color.luminance();
+        self.samples += 1;
+    }
+
+    pub fn mean(&self) -> Color {
+        if self.samples == 0 { Color::ZERO } else { self.sum / self.samples as f64 }
+    }
+
+    /// Standard error of the pixel as it shows up in the output image, on its zero to one scale. Half the
+    /// width of one standard error either side of the mean, after clamping and gamma correction, so
+    /// pixels far beyond white count as quiet.
+    pub fn noise(&self) -> f64 {
+        if self.samples < 2 {
+            return f64::INFINITY;
+        }
+
+        let n = self.samples as f64;
+        let mean = self.sum.luminance() / n;
+        let variance = ((self.luminance_squares / n - mean * mean) * n / (n - 1.0)).max(0.0);
+        let error = (variance / n).sqrt();
+
+        let display = |luminance: f64| luminance.clamp(0.0, 1.0).sqrt();
+        (display(mean + error) - display(mean - error)) / 2.0
+    }
+}
+
+/// Samples taken so far for every pixel, row by row from the top left.
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Pixel>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let pixel_count = Framebuffer::pixel_count(width, height).expect("Framebuffer is too large to address.");
+
+        Framebuffer { width, height, pixels: vec![Pixel::default(); pixel_count] }
+    }
+
+    /// Number of pixels in a `width` by `height` image, or `None` if it does not fit in memory indices.
+    pub fn pixel_count(width: u32, height: u32) -> Option<usize> {
+        (width as usize).checked_mul(height as usize)
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> &Pixel {
+        &self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    /// RMS noise over all pixels, on the same scale as `Pixel::noise`.
+    pub fn noise(&self) -> f64 {
+        let noise_squares = self.pixels.iter().map(|pixel| pixel.noise().powi(2)).sum::<f64>();
+
+        (noise_squares / self.pixels.len() as f64).sqrt()
+    }
+
+    /// Gamma corrected image, each pixel averaged over its own samples.
+    pub fn to_image(&self) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            let pixel = self.pixel(x, y);
+            pixel.sum.write_color(pixel.samples.max(1) as usize)
+        })
+    }
+
+    /// Samples per pixel from blue for none to red for `max_samples`.
+    pub fn sample_heatmap(&self, max_samples: u32) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            let t = (self.pixel(x, y).samples as f64 / max_samples as f64).clamp(0.0, 1.0);
+
+            // Blue to green to red.
+            let (r, g, b) = if t < 0.5 { (0.0, 2.0 * t, 1.0 - 2.0 * t) } else { (2.0 * t - 1.0, 2.0 - 2.0 * t, 0.0) };
+            Rgb([(255.0 * r) as u8, (255.0 * g) as u8, (255.0 * b) as u8])
+        })
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use crate::render::framebuffer::{Framebuffer, Pixel};
+
+pub mod checkpoint;
+pub mod framebuffer;
+pub mod tiles;
+
+/// When a pixel has had enough samples. Pixels get at least `min_samples` and stop once both their own
+/// noise and the RMS noise of their tile drop below `noise_threshold`, or at `max_samples`. Checking the
+/// tile too keeps pixels that happened to see no variance yet from stopping early. The threshold is the
+/// standard error of the final pixel value on a zero to one scale, so 0.01 is about 2.5 levels of an
+/// 8 bit image.
+#[derive(Copy, Clone)]
+pub struct AdaptiveSampling {
+    pub min_samples: u32,
+    pub max_samples: u32,
+    pub noise_threshold: f64,
+}
+
+impl AdaptiveSampling {
+    pub fn new(min_samples: u32, max_samples: u32, noise_threshold: f64) -> Self {
+        AdaptiveSampling { min_samples: min_samples.min(max_samples), max_samples, noise_threshold }
+    }
+
+    /// Every pixel gets exactly `samples` samples.
+    pub fn fixed(samples: u32) -> Self {
+        AdaptiveSampling::new(samples, samples, 0.0)
+    }
+
+    pub fn is_done(&self, pixel: &Pixel, tile_noise: f64) -> bool {
+        pixel.samples >= self.max_samples
+            || (pixel.samples >= self.min_samples && tile_noise < self.noise_threshold && pixel.noise() < self.noise_threshold)
+    }
+}
+
+/// Limits on a whole render, on top of the sample counts. Without any limits the render goes on until
+/// every pixel is done.
+#[derive(Copy, Clone, Default)]
+pub struct RenderBudget {
+    pub time_limit: Option<Duration>,
+    pub noise_target: Option<f64>,
+}
+
+impl RenderBudget {
+    /// Stops before a pass would likely end after `time_limit`, judging by how long the last one took.
+    pub fn with_time_limit(self, time_limit: Duration) -> Self {
+        RenderBudget { time_limit: Some(time_limit), ..self }
+    }
+
+    /// Stops once the RMS noise of the image drops below `noise_target`, on the scale of `AdaptiveSampling`.
+    pub fn with_noise_target(self, noise_target: f64) -> Self {
+        RenderBudget { noise_target: Some(noise_target), ..self }
+    }
+
+    pub fn is_spent(&self, elapsed: Duration, last_pass: Duration, framebuffer: &Framebuffer) -> bool {
+        let out_of_time = self.time_limit.is_some_and(|limit| elapsed + last_pass > limit);
+        let quiet_enough = self.noise_target.is_some_and(|target| framebuffer.noise() < target);
+
+        out_of_time || quiet_enough
+    }
+}
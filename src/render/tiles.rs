@@ -0,0 +1,186 @@
+use rayon::prelude::*;
+
+use crate::data_structs::vec3::Color;
+use crate::render::AdaptiveSampling;
+use crate::render::framebuffer::Framebuffer;
+
+/// Rectangle of pixels rendered as one unit of work. `x` and `y` are the top left corner.
+#[derive(Copy, Clone)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Order in which tiles are handed to the workers.
+#[derive(Copy, Clone, Debug)]
+pub enum TileOrder {
+    /// Row by row from the top left.
+    Scanline,
+    /// Square spiral outwards from the center, so the middle of the image shows up first.
+    Spiral,
+    /// Along a Hilbert curve, keeping consecutive tiles next to each other.
+    Hilbert,
+}
+
+impl TileOrder {
+    /// Grid cells as `(column, row)` in this order.
+    fn cells(&self, columns: u32, rows: u32) -> Vec<(u32, u32)> {
+        match self {
+            TileOrder::Scanline => (0..rows).flat_map(|row| (0..columns).map(move |column| (column, row))).collect(),
+            TileOrder::Spiral => spiral(columns, rows),
+            TileOrder::Hilbert => hilbert(columns, rows),
+        }
+    }
+}
+
+/// Splits the image into square tiles and renders them on the rayon workers in a chosen order.
+pub struct TileSchedule {
+    width: u32,
+    height: u32,
+    tile_size: u32,
+    columns: u32,
+    tiles: Vec<Tile>,
+    /// Position in `tiles` of every grid cell, row by row.
+    order: Vec<usize>,
+}
+
+impl TileSchedule {
+    pub fn new(width: u32, height: u32, tile_size: u32, tile_order: TileOrder) -> Self {
+        let columns = width.div_ceil(tile_size);
+        let rows = height.div_ceil(tile_size);
+
+        let cells = tile_order.cells(columns, rows);
+        let mut order = vec![0; cells.len()];
+        let tiles = cells
+            .iter()
+            .enumerate()
+            .map(|(position, &(column, row))| {
+                order[(row * columns + column) as usize] = position;
+
+                let (x, y) = (column * tile_size, row * tile_size);
+                Tile { x, y, width: tile_size.min(width - x), height: tile_size.min(height - y) }
+            })
+            .collect();
+
+        TileSchedule { width, height, tile_size, columns, tiles, order }
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    /// Adds one sample from `trace(x, y)` to every pixel the sampling settings are not done with. Workers
+    /// pick up tiles in schedule order and write straight into their part of the framebuffer. Returns
+    /// how many pixels still need samples afterwards.
+    pub fn render_pass<F>(&self, framebuffer: &mut Framebuffer, sampling: &AdaptiveSampling, trace: F) -> usize
+    where
+        F: Fn(u32, u32) -> Color + Sync,
+    {
+        assert!(framebuffer.width == self.width && framebuffer.height == self.height, "Framebuffer size does not match the tiles.");
+
+        self.split(&mut framebuffer.pixels)
+            .into_iter()
+            .zip(self.tiles.iter())
+            .par_bridge()
+            .map(|(rows, tile)| {
+                let noise_squares = rows.iter().flat_map(|row| row.iter()).map(|pixel| pixel.noise().powi(2)).sum::<f64>();
+                let tile_noise = (noise_squares / (tile.width * tile.height) as f64).sqrt();
+                let mut active = 0;
+
+                for (j, row) in rows.into_iter().enumerate() {
+                    for (i, pixel) in row.iter_mut().enumerate() {
+                        if sampling.is_done(pixel, tile_noise) {
+                            continue;
+                        }
+
+                        pixel.add(trace(tile.x + i as u32, tile.y + j as u32));
+                        if !sampling.is_done(pixel, tile_noise) {
+                            active += 1;
+                        }
+                    }
+                }
+
+                active
+            })
+            .sum()
+    }
+
+    /// Borrows the rows of every tile from the framebuffer, in schedule order.
+    fn split<'a, T>(&self, pixels: &'a mut [T]) -> Vec<Vec<&'a mut [T]>> {
+        let mut tiles = self.tiles.iter().map(|tile| Vec::with_capacity(tile.height as usize)).collect::<Vec<_>>();
+
+        for (y, row) in pixels.chunks_mut(self.width as usize).enumerate() {
+            let grid_row = y as u32 / self.tile_size;
+
+            for (column, part) in row.chunks_mut(self.tile_size as usize).enumerate() {
+                tiles[self.order[(grid_row * self.columns) as usize + column]].push(part);
+            }
+        }
+
+        tiles
+    }
+}
+
+fn spiral(columns: u32, rows: u32) -> Vec<(u32, u32)> {
+    let total = (columns * rows) as usize;
+    let mut cells = Vec::with_capacity(total);
+
+    let (mut x, mut y) = ((columns as i64 - 1) / 2, (rows as i64 - 1) / 2);
+    let directions = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+    let mut leg = 0;
+
+    // Legs of length 1, 1, 2, 2, 3, 3... turning after each. Cells outside the grid are skipped.
+    while cells.len() < total {
+        let (dx, dy) = directions[leg % 4];
+        let length = leg / 2 + 1;
+
+        for _ in 0..length {
+            if x >= 0 && y >= 0 && x < columns as i64 && y < rows as i64 {
+                cells.push((x as u32, y as u32));
+            }
+            x += dx;
+            y += dy;
+        }
+        leg += 1;
+    }
+
+    cells
+}
+
+fn hilbert(columns: u32, rows: u32) -> Vec<(u32, u32)> {
+    let size = columns.max(rows).next_power_of_two();
+
+    (0..size * size)
+        .map(|distance| hilbert_cell(size, distance))
+        .filter(|&(x, y)| x < columns && y < rows)
+        .collect()
+}
+
+/// Cell at `distance` along the Hilbert curve filling a `size` by `size` grid.
+fn hilbert_cell(size: u32, distance: u32) -> (u32, u32) {
+    let (mut x, mut y) = (0, 0);
+    let mut t = distance;
+    let mut s = 1;
+
+    while s < size {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+
+    (x, y)
+}
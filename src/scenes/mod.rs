@@ -1,29 +1,53 @@
-use rand::{random, Rng, thread_rng};
+use std::sync::Arc;
 
+use rand::Rng;
+
+use crate::data_structs::random::{random, rng};
 use crate::data_structs::vec3::{Color, Point3, Vec3};
+use crate::lights::{Background, LightLinks, LightList};
+use crate::lights::delta_light::DeltaLight;
+use crate::lights::environment::EnvironmentMap;
+use crate::lights::ies::IesProfile;
+use crate::lights::sky::Sky;
+use crate::lights::sphere_light::SphereLight;
+use crate::materials::alpha_masked::{AlphaMasked, AlphaMode};
+use crate::materials::anisotropic_metal::AnisotropicMetal;
 use crate::materials::dielectric::Dielectric;
 use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::dispersive_dielectric::DispersiveDielectric;
+use crate::materials::emission::{Emission, Emissive};
 use crate::materials::lambertian::Lambertian;
 use crate::materials::Material;
 use crate::materials::metal::Metal;
+use crate::materials::normal_mapped::NormalMapped;
+use crate::materials::subsurface::Subsurface;
+use crate::materials::thin_dielectric::ThinDielectric;
 use crate::materials::textures::checker_texture::CheckerTexture;
 use crate::materials::textures::image_texture::ImageTexture;
+use crate::materials::textures::normal_map::NormalMap;
 use crate::materials::textures::perlin::NoiseTexture;
-use crate::objects::camera::Camera;
+use crate::objects::aperture::Aperture;
+use crate::objects::camera::{Camera, FisheyeMapping, ProjectionCamera, StereoLayout};
 use crate::objects::hittables::bvh::BVHNode;
+use crate::objects::physical_camera::PhysicalCamera;
+use crate::objects::lens_camera::{LensCamera, LensElement};
 use crate::objects::hittables::constant_medium::ConstantMedium;
 use crate::objects::hittables::cube::Cube;
 use crate::objects::hittables::HittableList;
+use crate::objects::hittables::instances::flip_face::FlipFace;
 use crate::objects::hittables::instances::rotate_y::RotateY;
 use crate::objects::hittables::instances::translate::Translate;
+use crate::objects::hittables::instances::visibility::{Visibility, VisibilityFlags};
 use crate::objects::hittables::moving_sphere::MovingSphere;
 use crate::objects::hittables::rectangles::xy_rectangle::XyRectangle;
 use crate::objects::hittables::rectangles::xz_rectangle::XzRectangle;
 use crate::objects::hittables::rectangles::yz_rectangle::YzRectangle;
 use crate::objects::hittables::sphere::Sphere;
+use crate::objects::hittables::subsurface_medium::SubsurfaceMedium;
 
-fn one_weekend_scene(image_width: u32, image_height: u32) -> (Color, Camera, HittableList) {
-    let background_color = Color::new(0.7, 0.8, 1.0);
+fn one_weekend_scene(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let mut lights = LightList::new();
+    let background_color = lights.add_sky(Sky::new(35.0, 200.0, 3.0));
 
     let look_from = Point3::new(13.0, 2.0, 3.0);
     let look_at = Point3::new(0.0, 0.0, 0.0);
@@ -34,7 +58,7 @@ fn one_weekend_scene(image_width: u32, image_height: u32) -> (Color, Camera, Hit
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -44,7 +68,7 @@ fn one_weekend_scene(image_width: u32, image_height: u32) -> (Color, Camera, Hit
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
     let mut world = HittableList::new();
 
@@ -53,8 +77,8 @@ fn one_weekend_scene(image_width: u32, image_height: u32) -> (Color, Camera, Hit
 
     for a in -11..11 {
         for b in -11..11 {
-            let choose_material = rand::random::<f64>();
-            let center = Point3::new(a as f64 + 0.9 * rand::random::<f64>(), 0.2, b as f64 + 0.9 * rand::random::<f64>());
+            let choose_material = random::<f64>();
+            let center = Point3::new(a as f64 + 0.9 * random::<f64>(), 0.2, b as f64 + 0.9 * random::<f64>());
 
             if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
                 let material = if choose_material < 0.8 {
@@ -85,12 +109,160 @@ fn one_weekend_scene(image_width: u32, image_height: u32) -> (Color, Camera, Hit
     // world.add(Sphere::new(Point3::new(4.0, 0.7, 2.5), 0.7, material_dielectric.clone()));
     // world.add(Sphere::new(Point3::new(4.0, 0.7, 2.5), -0.65, material_dielectric));
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
+}
+
+
+fn many_lights(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (_, camera, _, _) = one_weekend_scene(image_width, image_height);
+    let background_color = Background::Color(Color::new(0.0, 0.0, 0.0));
+
+    let mut world = HittableList::new();
+    let mut lights = LightList::new();
+
+    let ground_material = Material::Lambertian(Lambertian::from_color(Color::new(0.5, 0.5, 0.5)));
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground_material));
+
+    // Every emitter is registered so it gets picked by its power and distance.
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_material = random::<f64>();
+            let center = Point3::new(a as f64 + 0.9 * random::<f64>(), 0.2, b as f64 + 0.9 * random::<f64>());
+
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                if choose_material < 0.3 {
+                    let radiance = Color::random_with_limits(0.2, 1.0) * 4.0 * random::<f64>();
+
+                    lights.add_emitter(&mut world, SphereLight::new(center, 0.2, radiance));
+                } else {
+                    let albedo = Color::random() * Color::random();
+
+                    world.add(Sphere::new(center, 0.2, Material::Lambertian(Lambertian::from_color(albedo))));
+                }
+            }
+        }
+    }
+
+    let material_lambertian = Material::Lambertian(Lambertian::from_color(Color::new(0.4, 0.2, 0.1)));
+    let material_metal = Material::Metal(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+
+    world.add(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, Material::Lambertian(Lambertian::from_color(Color::new(0.73, 0.73, 0.73)))));
+    world.add(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material_lambertian));
+    world.add(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material_metal));
+
+    (background_color, camera, world, lights)
+}
+
+fn one_weekend_photograph(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, _, world, lights) = one_weekend_scene(image_width, image_height);
+
+    let look_from = Point3::new(13.0, 2.0, 3.0);
+    let look_at = Point3::new(0.0, 1.0, 0.0);
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let aspect_ratio = image_width as f64 / image_height as f64;
+
+    // 85 mm portrait lens wide open in daylight.
+    let camera = PhysicalCamera::new(look_from, look_at, up, aspect_ratio)
+        .with_focal_length(85.0)
+        .with_f_stop(1.8)
+        .with_shutter(0.0, 1.0 / 8000.0)
+        .with_iso(100.0);
+
+    (background_color, Arc::new(camera), world, lights)
+}
+
+fn one_weekend_lens_system(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, _, world, lights) = one_weekend_scene(image_width, image_height);
+
+    let look_from = Point3::new(13.0, 2.0, 3.0);
+    let look_at = Point3::new(0.0, 1.0, 0.0);
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let aspect_ratio = image_width as f64 / image_height as f64;
+
+    let elements = LensElement::from_file("src/lens_prescriptions/double_gauss_50mm.dat");
+    let camera = LensCamera::new(look_from, look_at, up, aspect_ratio, elements).expect("Could not focus the lens.");
+
+    (background_color, Arc::new(camera), world, lights)
+}
+
+fn one_weekend_tilt_shift(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, _, world, lights) = one_weekend_scene(image_width, image_height);
+
+    let look_from = Point3::new(13.0, 5.0, 3.0);
+    let look_at = Point3::new(0.0, 1.0, 0.0);
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let distance_to_focus = (look_at - look_from).length();
+    let aspect_ratio = image_width as f64 / image_height as f64;
+
+    // Focal plane tilted against the ground, so only a band through the middle spheres is sharp.
+    let camera = ProjectionCamera::new(look_from, look_at, up, 20.0, aspect_ratio, 0.8, distance_to_focus, 0.0, 1.0)
+        .with_shift(0.0, -0.1)
+        .with_tilt(-30.0, 0.0);
+
+    (background_color, Arc::new(camera), world, lights)
+}
+
+fn one_weekend_panorama(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, _, world, lights) = one_weekend_scene(image_width, image_height);
+
+    let look_from = Point3::new(2.0, 1.0, 2.5);
+    let look_at = Point3::new(0.0, 1.0, 0.0);
+    let up = Vec3::new(0.0, 1.0, 0.0);
+
+    let camera = Arc::new(ProjectionCamera::equirectangular(look_from, look_at, up));
+
+    (background_color, camera, world, lights)
+}
+
+fn one_weekend_stereo_panorama(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, _, world, lights) = one_weekend_panorama(image_width, image_height);
+
+    let look_from = Point3::new(2.0, 1.0, 2.5);
+    let look_at = Point3::new(0.0, 1.0, 0.0);
+    let up = Vec3::new(0.0, 1.0, 0.0);
+
+    // Over/under omni-directional stereo for VR headsets. Use a square image.
+    let camera = Arc::new(ProjectionCamera::equirectangular(look_from, look_at, up)
+        .with_stereo(0.065, f64::INFINITY, StereoLayout::OverUnder));
+
+    (background_color, camera, world, lights)
 }
 
+fn one_weekend_fisheye(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, _, world, lights) = one_weekend_scene(image_width, image_height);
 
-fn movable_one_weekend(image_width: u32, image_height: u32) -> (Color, Camera, HittableList) {
-    let background_color = Color::new(0.7, 0.8, 1.0);
+    let look_from = Point3::new(2.0, 0.5, 2.5);
+    let look_at = Point3::new(2.0, 10.0, 2.5);
+    let up = Vec3::new(0.0, 0.0, -1.0);
+    let aspect_ratio = image_width as f64 / image_height as f64;
+
+    let camera = Arc::new(ProjectionCamera::fisheye(look_from, look_at, up, 180.0, FisheyeMapping::Equidistant, aspect_ratio));
+
+    (background_color, camera, world, lights)
+}
+
+fn bokeh(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, _, world, lights) = many_lights(image_width, image_height);
+
+    let look_from = Point3::new(13.0, 2.0, 3.0);
+    let look_at = Point3::new(4.0, 1.0, 0.0);
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let aspect_ratio = image_width as f64 / image_height as f64;
+
+    // Focused in front of the spheres. Six aperture blades turn the out of focus lights into hexagons.
+    let camera = PhysicalCamera::new(look_from, look_at, up, aspect_ratio)
+        .with_focal_length(85.0)
+        .with_f_stop(1.4)
+        .with_focus_distance(3.0)
+        .with_shutter(0.0, 1.0 / 8000.0)
+        .with_iso(64.0)
+        .with_aperture_shape(Aperture::polygon(6, 15.0).with_cat_eye(0.5));
+
+    (background_color, Arc::new(camera), world, lights)
+}
+
+fn movable_one_weekend(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let background_color = Background::Color(Color::new(0.7, 0.8, 1.0));
 
     let look_from = Point3::new(13.0, 2.0, 3.0);
     let look_at = Point3::new(0.0, 0.0, 0.0);
@@ -101,7 +273,7 @@ fn movable_one_weekend(image_width: u32, image_height: u32) -> (Color, Camera, H
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -111,7 +283,7 @@ fn movable_one_weekend(image_width: u32, image_height: u32) -> (Color, Camera, H
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
     let mut world = HittableList::new();
 
@@ -126,7 +298,7 @@ fn movable_one_weekend(image_width: u32, image_height: u32) -> (Color, Camera, H
     for a in -11..11 {
         for b in -11..11 {
             let choose_material = random::<f64>();
-            let center = Point3::new(a as f64 + 0.9 * random::<f64>(), 0.2, b as f64 + 0.9 * rand::random::<f64>());
+            let center = Point3::new(a as f64 + 0.9 * random::<f64>(), 0.2, b as f64 + 0.9 * random::<f64>());
             let mut is_movable = false;
 
             if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
@@ -165,11 +337,11 @@ fn movable_one_weekend(image_width: u32, image_height: u32) -> (Color, Camera, H
     // world.add(Sphere::new(Point3::new(4.0, 0.7, 2.5), 0.7, material_dielectric.clone()));
     // world.add(Sphere::new(Point3::new(4.0, 0.7, 2.5), -0.65, material_dielectric));
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
 }
 
-fn two_textured_spheres_scene(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
-    let background_color = Color::new(0.7, 0.8, 1.0);
+fn two_textured_spheres_scene(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let background_color = Background::Color(Color::new(0.7, 0.8, 1.0));
 
     let look_from = Point3::new(13.0, 2.0, 3.0);
     let look_at = Point3::new(0.0, 0.0, 0.0);
@@ -180,7 +352,7 @@ fn two_textured_spheres_scene(image_width: u32, image_height: u32) -> (Vec3, Cam
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -190,7 +362,7 @@ fn two_textured_spheres_scene(image_width: u32, image_height: u32) -> (Vec3, Cam
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
     let mut world = HittableList::new();
 
@@ -203,11 +375,11 @@ fn two_textured_spheres_scene(image_width: u32, image_height: u32) -> (Vec3, Cam
     world.add(Sphere::new(Point3::new(0.0, -10.0, 0.0), 10.0, Material::Lambertian(checker_material.clone())));
     world.add(Sphere::new(Point3::new(0.0, 10.0, 0.0), 10.0, Material::Lambertian(checker_material)));
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
 }
 
-fn two_perlin_spheres(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
-    let background_color = Color::new(0.7, 0.8, 1.0);
+fn two_perlin_spheres(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let background_color = Background::Color(Color::new(0.7, 0.8, 1.0));
 
     let look_from = Point3::new(13.0, 2.0, 3.0);
     let look_at = Point3::new(0.0, 0.0, 0.0);
@@ -218,7 +390,7 @@ fn two_perlin_spheres(image_width: u32, image_height: u32) -> (Vec3, Camera, Hit
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -228,7 +400,7 @@ fn two_perlin_spheres(image_width: u32, image_height: u32) -> (Vec3, Camera, Hit
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
     let mut world = HittableList::new();
 
@@ -237,11 +409,185 @@ fn two_perlin_spheres(image_width: u32, image_height: u32) -> (Vec3, Camera, Hit
     world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Material::Lambertian(perlin_texture.clone())));
     world.add(Sphere::new(Point3::new(0.0, 2.0, 0.0), 2.0, Material::Lambertian(perlin_texture)));
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
+}
+
+fn bump_mapped_spheres(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, _, _) = two_perlin_spheres(image_width, image_height);
+
+    let mut world = HittableList::new();
+
+    let bumpy_ground = NormalMapped::new(
+        Material::Lambertian(Lambertian::from_color(Color::new(0.5, 0.5, 0.5))),
+        NormalMap::bump(NoiseTexture::new(4.0), 0.05),
+    );
+    let bumpy_metal = NormalMapped::new(
+        Material::Metal(Metal::new(Color::new(0.8, 0.8, 0.9), 0.0)),
+        NormalMap::bump(NoiseTexture::new(4.0), 0.1),
+    );
+
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Material::NormalMapped(bumpy_ground)));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, 0.0), 2.0, Material::NormalMapped(bumpy_metal)));
+
+    (background_color, camera, world, LightList::new())
+}
+
+fn brushed_metal(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, _, _) = two_perlin_spheres(image_width, image_height);
+
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new_texture(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let brushed_aluminium = AnisotropicMetal::from_color(Color::new(0.91, 0.92, 0.92), 0.05, 0.4);
+    let satin = AnisotropicMetal::from_color(Color::new(0.95, 0.64, 0.54), 0.4, 0.05);
+
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Material::Lambertian(ground)));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, -2.2), 2.0, Material::AnisotropicMetal(brushed_aluminium)));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, 2.2), 2.0, Material::AnisotropicMetal(satin)));
+
+    (background_color, camera, world, LightList::new())
+}
+
+fn orthographic_product_shot(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, _, world, lights) = brushed_metal(image_width, image_height);
+
+    let look_from = Point3::new(20.0, 20.0, 20.0);
+    let look_at = Point3::new(0.0, 1.0, 0.0);
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let view_height = 9.0;
+    let aspect_ratio = image_width as f64 / image_height as f64;
+
+    let camera = Arc::new(ProjectionCamera::orthographic(look_from, look_at, up, view_height, aspect_ratio));
+
+    (background_color, camera, world, lights)
+}
+
+fn thin_films(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, _, _) = two_perlin_spheres(image_width, image_height);
+
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new_texture(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let soap_bubble = ThinDielectric::new(1.33).with_thin_film(450.0);
+    let window_pane = ThinDielectric::new(1.5);
+    let coated_metal = Metal::new(Color::new(0.8, 0.8, 0.8), 0.0).with_thin_film(350.0, 2.0);
+
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Material::Lambertian(ground)));
+    world.add(Sphere::new(Point3::new(0.0, 1.5, -2.0), 1.5, Material::ThinDielectric(soap_bubble)));
+    world.add(Sphere::new(Point3::new(0.0, 1.5, 2.0), 1.5, Material::Metal(coated_metal)));
+    world.add(YzRectangle::new(0.0, 4.0, -4.0, 4.0, 6.0, Material::ThinDielectric(window_pane)));
+
+    (background_color, camera, world, LightList::new())
+}
+
+fn alpha_masked_quad(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, mut world, lights) = two_perlin_spheres(image_width, image_height);
+
+    let fence = AlphaMasked::new(
+        Material::Lambertian(Lambertian::from_color(Color::new(0.6, 0.4, 0.2))),
+        CheckerTexture::new_from_color(Color::ZERO, Color::ONE),
+        AlphaMode::Threshold(0.5),
+    );
+
+    world.add(YzRectangle::new(0.0, 4.0, -3.0, 3.0, 4.0, Material::AlphaMasked(fence)));
+
+    (background_color, camera, world, lights)
+}
+
+fn environment_lit(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (_, camera, _, _) = two_perlin_spheres(image_width, image_height);
+    let background = Background::from_environment(EnvironmentMap::new("src/image_textures/earthmap.jpg", 1.5, 90.0));
+
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::from_color(Color::new(0.73, 0.73, 0.73));
+    let mirror = Metal::new(Color::new(0.9, 0.9, 0.9), 0.0);
+
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Material::Lambertian(ground.clone())));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, -2.2), 2.0, Material::Lambertian(ground)));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, 2.2), 2.0, Material::Metal(mirror)));
+
+    (background, camera, world, LightList::new())
+}
+
+fn delta_lights(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (_, camera, _, _) = two_perlin_spheres(image_width, image_height);
+    let background_color = Background::Color(Color::new(0.01, 0.01, 0.02));
+
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new_texture(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let white = Lambertian::from_color(Color::new(0.73, 0.73, 0.73));
+
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Material::Lambertian(ground)));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, -2.2), 2.0, Material::Lambertian(white.clone())));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, 2.2), 2.0, Material::Lambertian(white)));
+
+    let mut lights = LightList::new();
+    lights.add(DeltaLight::point(Point3::new(4.0, 5.0, 0.0), Color::new(40.0, 30.0, 20.0)));
+    lights.add(DeltaLight::spot(
+        Point3::new(6.0, 8.0, 6.0),
+        Point3::new(0.0, 0.0, 2.2),
+        Color::new(60.0, 80.0, 120.0),
+        10.0,
+        20.0,
+    ));
+    lights.add(DeltaLight::directional(Vec3::new(-1.0, -1.0, -0.5), Color::new(0.15, 0.15, 0.2)));
+
+    (background_color, camera, world, lights)
+}
+
+fn light_linking(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, _, _) = delta_lights(image_width, image_height);
+
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new_texture(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let white = Lambertian::from_color(Color::new(0.73, 0.73, 0.73));
+    let mirror = Metal::new(Color::new(0.9, 0.9, 0.9), 0.0);
+
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Material::Lambertian(ground)));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, 2.2), 2.0, Material::Metal(mirror)));
+
+    // Only the key light reaches this sphere.
+    let key_lit = Sphere::new(Point3::new(0.0, 2.0, -2.2), 2.0, Material::Lambertian(white.clone()));
+    world.add(Visibility::new(key_lit, VisibilityFlags::ALL).with_light_group(1));
+
+    // Hidden from the camera, but it still casts a shadow and shows up in the mirror.
+    let hidden = Sphere::new(Point3::new(3.0, 0.7, 0.0), 0.7, Material::Lambertian(white));
+    world.add(Visibility::new(hidden, VisibilityFlags { camera: false, ..VisibilityFlags::ALL }));
+
+    let mut lights = LightList::new();
+    lights.add_linked(
+        DeltaLight::point(Point3::new(4.0, 5.0, -4.0), Color::new(60.0, 40.0, 20.0)),
+        LightLinks::Include(vec![1]),
+    );
+    lights.add_linked(
+        DeltaLight::point(Point3::new(6.0, 5.0, 2.0), Color::new(30.0, 40.0, 60.0)),
+        LightLinks::Exclude(vec![1]),
+    );
+
+    // A glowing sphere that lights everything but the key lit sphere.
+    let glow = SphereLight::new(Point3::new(-3.0, 0.5, 0.0), 0.5, Color::new(8.0, 2.0, 2.0));
+    lights.add_linked_emitter(&mut world, glow, LightLinks::Exclude(vec![1]));
+
+    (background_color, camera, world, lights)
 }
 
-fn earth(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
-    let background_color = Color::new(0.7, 0.8, 1.0);
+fn earth(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let background_color = Background::Color(Color::new(0.7, 0.8, 1.0));
 
     let look_from = Point3::new(0.0, 0.0, 12.0);
     let look_at = Point3::new(0.0, 0.0, 0.0);
@@ -252,7 +598,7 @@ fn earth(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -262,19 +608,19 @@ fn earth(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
     let mut world = HittableList::new();
 
     let earth_texture = Lambertian::new_texture(ImageTexture::new("src/image_textures/earthmap.jpg"));
     world.add(Sphere::new(Point3::new(0.0, 0.0, 0.0), 2.0, Material::Lambertian(earth_texture)));
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
 }
 
-fn diffuse_light(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
-    let (_, _, mut world) = two_perlin_spheres(image_width, image_height);
-    let background_color = Color::new(0.0, 0.0, 0.0);
+fn diffuse_light(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (_, _, mut world, _) = two_perlin_spheres(image_width, image_height);
+    let background_color = Background::Color(Color::new(0.0, 0.0, 0.0));
 
     let look_from = Point3::new(26.0, 3.0, 6.0);
     let look_at = Point3::new(0.0, 2.0, 0.0);
@@ -285,7 +631,7 @@ fn diffuse_light(image_width: u32, image_height: u32) -> (Vec3, Camera, Hittable
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -295,7 +641,7 @@ fn diffuse_light(image_width: u32, image_height: u32) -> (Vec3, Camera, Hittable
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
 
     let light_strength = 4.0;
@@ -303,11 +649,28 @@ fn diffuse_light(image_width: u32, image_height: u32) -> (Vec3, Camera, Hittable
     world.add(XyRectangle::new(3.0, 5.0, 1.0, 3.0, -2.0, Material::DiffuseLight(diffuse_light.clone())));
     world.add(Sphere::new(Point3::new(0.0, 7.0, 0.0), 2.0, Material::DiffuseLight(diffuse_light)));
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
+}
+
+fn dispersion(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, _, _) = diffuse_light(image_width, image_height);
+
+    let mut world = HittableList::new();
+
+    let floor = Lambertian::from_color(Color::new(0.8, 0.8, 0.8));
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Material::Lambertian(floor)));
+
+    world.add(Sphere::new(Point3::new(0.0, 2.0, 0.0), 2.0, Material::DispersiveDielectric(DispersiveDielectric::diamond())));
+    world.add(Sphere::new(Point3::new(0.0, 1.0, 4.0), 1.0, Material::DispersiveDielectric(DispersiveDielectric::bk7())));
+
+    let light = DiffuseLight::from_color(Color::new(40.0, 40.0, 40.0));
+    world.add(XyRectangle::new(-0.5, 0.5, 5.0, 6.0, -6.0, Material::DiffuseLight(light)));
+
+    (background_color, camera, world, LightList::new())
 }
 
-fn cornell_box(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
-    let background_color = Color::new(0.0, 0.0, 0.0);
+fn cornell_box(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let background_color = Background::Color(Color::new(0.0, 0.0, 0.0));
 
     let look_from = Point3::new(278.0, 278.0, -800.0);
     let look_at = Point3::new(278.0, 278.0, 0.0);
@@ -318,7 +681,7 @@ fn cornell_box(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableLi
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -328,18 +691,18 @@ fn cornell_box(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableLi
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
     let mut world = HittableList::new();
 
     let red = Lambertian::from_color(Color::new(0.65, 0.05, 0.05));
     let white = Lambertian::from_color(Color::new(0.73, 0.73, 0.73));
     let green = Lambertian::from_color(Color::new(0.12, 0.45, 0.15));
-    let light = DiffuseLight::from_color(Color::new(15.0, 15.0, 15.0));
+    let light = DiffuseLight::from_emission(Emission::from_color(Color::ONE, 15.0).one_sided());
 
     world.add(YzRectangle::new(0.0, 555.0, 0.0, 555.0, 555.0, Material::Lambertian(green.clone())));
     world.add(YzRectangle::new(0.0, 555.0, 0.0, 555.0, 0.0, Material::Lambertian(red.clone())));
-    world.add(XzRectangle::new(213.0, 343.0, 227.0, 332.0, 554.0, Material::DiffuseLight(light.clone())));
+    world.add(FlipFace::new(XzRectangle::new(213.0, 343.0, 227.0, 332.0, 554.0, Material::DiffuseLight(light))));
     world.add(XzRectangle::new(0.0, 555.0, 0.0, 555.0, 0.0, Material::Lambertian(white.clone())));
     world.add(XzRectangle::new(0.0, 555.0, 0.0, 555.0, 555.0, Material::Lambertian(white.clone())));
     world.add(XyRectangle::new(0.0, 555.0, 0.0, 555.0, 555.0, Material::Lambertian(white.clone())));
@@ -366,11 +729,41 @@ fn cornell_box(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableLi
     world.add(cube_1);
     world.add(cube_2);
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
 }
 
-fn cornell_smoke(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
-    let background_color = Color::new(0.0, 0.0, 0.0);
+fn cornell_downlights(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, _, _) = cornell_box(image_width, image_height);
+
+    let mut world = HittableList::new();
+
+    let red = Lambertian::from_color(Color::new(0.65, 0.05, 0.05));
+    let white = Lambertian::from_color(Color::new(0.73, 0.73, 0.73));
+    let green = Lambertian::from_color(Color::new(0.12, 0.45, 0.15));
+
+    world.add(YzRectangle::new(0.0, 555.0, 0.0, 555.0, 555.0, Material::Lambertian(green)));
+    world.add(YzRectangle::new(0.0, 555.0, 0.0, 555.0, 0.0, Material::Lambertian(red)));
+    world.add(XzRectangle::new(0.0, 555.0, 0.0, 555.0, 0.0, Material::Lambertian(white.clone())));
+    world.add(XzRectangle::new(0.0, 555.0, 0.0, 555.0, 555.0, Material::Lambertian(white.clone())));
+    world.add(XyRectangle::new(0.0, 555.0, 0.0, 555.0, 555.0, Material::Lambertian(white.clone())));
+
+    let cube = Cube::new(Point3::new(0.0, 0.0, 0.0), Point3::new(165.0, 330.0, 165.0), Material::Lambertian(white));
+    let cube = RotateY::new(cube, 15.0);
+    world.add(Translate::new(cube, Vec3::new(265.0, 0.0, 295.0)));
+
+    // Candela values from the file are scaled to scene units.
+    let mut lights = LightList::new();
+    for x in [140.0, 415.0] {
+        let downlight = DeltaLight::point(Point3::new(x, 545.0, 450.0), Color::new(150.0, 140.0, 120.0))
+            .with_profile(IesProfile::new("src/light_profiles/downlight.ies"));
+        lights.add(downlight);
+    }
+
+    (background_color, camera, world, lights)
+}
+
+fn cornell_smoke(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let background_color = Background::Color(Color::new(0.0, 0.0, 0.0));
 
     let look_from = Point3::new(278.0, 278.0, -800.0);
     let look_at = Point3::new(278.0, 278.0, 0.0);
@@ -381,7 +774,7 @@ fn cornell_smoke(image_width: u32, image_height: u32) -> (Vec3, Camera, Hittable
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -391,7 +784,7 @@ fn cornell_smoke(image_width: u32, image_height: u32) -> (Vec3, Camera, Hittable
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
     let mut world = HittableList::new();
 
@@ -429,11 +822,43 @@ fn cornell_smoke(image_width: u32, image_height: u32) -> (Vec3, Camera, Hittable
     world.add(ConstantMedium::from_color(cube_1, 0.01, Color::ZERO));
     world.add(ConstantMedium::from_color(cube_2, 0.01, Color::ONE));
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
 }
 
-fn final_scene(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableList) {
-    let background_color = Color::new(0.0, 0.0, 0.0);
+fn glowing_screen(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, mut world, lights) = cornell_box(image_width, image_height);
+
+    // Checkered picture behind a glass front. The screen only glows towards the camera.
+    let picture = Emission::from_color(Color::new(0.9, 0.9, 1.0), 4.0)
+        .with_multiplier(CheckerTexture::new_from_color(Color::new(0.2, 0.2, 0.2), Color::ONE))
+        .one_sided();
+    let screen = Emissive::new(Material::Dielectric(Dielectric::new(1.5)), picture);
+
+    world.add(FlipFace::new(XyRectangle::new(150.0, 400.0, 200.0, 380.0, 554.0, Material::Emissive(screen))));
+
+    (background_color, camera, world, lights)
+}
+
+fn cornell_subsurface(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let (background_color, camera, mut world, lights) = cornell_box(image_width, image_height);
+
+    // Wax-like sphere resting on the short box. Red light travels furthest below the surface.
+    let wax = Subsurface::from_color(
+        Color::new(0.95, 0.8, 0.6),
+        Color::new(30.0, 12.0, 6.0),
+        1.4,
+    );
+
+    world.add(SubsurfaceMedium::new(
+        Sphere::new(Point3::new(212.0, 235.0, 147.0), 70.0, Material::default()),
+        wax,
+    ));
+
+    (background_color, camera, world, lights)
+}
+
+fn final_scene(image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
+    let background_color = Background::Color(Color::new(0.0, 0.0, 0.0));
 
     let look_from = Point3::new(478.0, 278.0, -600.0);
     let look_at = Point3::new(278.0, 278.0, 0.0);
@@ -444,7 +869,7 @@ fn final_scene(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableLi
 
     let aspect_ratio = image_width as f64 / image_height as f64;
 
-    let camera = Camera::new(
+    let camera = Arc::new(ProjectionCamera::new(
         look_from,
         look_at,
         up,
@@ -454,9 +879,9 @@ fn final_scene(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableLi
         distance_to_focus,
         0.0,
         1.0
-    );
+    ));
 
-    let mut rng = thread_rng();
+    let mut rng = rng();
     let mut world = HittableList::new();
 
     // Ground boxes.
@@ -570,32 +995,73 @@ fn final_scene(image_width: u32, image_height: u32) -> (Vec3, Camera, HittableLi
     //     Vec3::new(-100.0, 270.0, 395.0)
     // ));
 
-    (background_color, camera, world)
+    (background_color, camera, world, LightList::new())
 }
 
 
+#[derive(Copy, Clone, Debug)]
 pub enum WorldEnum {
     OneWeekendScene,
+    PhotographScene,
+    LensSystemScene,
+    TiltShiftScene,
+    PanoramaScene,
+    StereoPanoramaScene,
+    FisheyeScene,
+    ManyLightsScene,
+    BokehScene,
     MovableWeekendScene,
     TwoTexturedSpheresScene,
     TwoPerlinSpheresScene,
+    BumpMappedScene,
+    AlphaMaskedScene,
+    BrushedMetalScene,
+    OrthographicScene,
+    ThinFilmScene,
+    EnvironmentScene,
+    DeltaLightsScene,
+    LightLinkingScene,
     EarthScene,
     DiffuseLightScene,
+    DispersionScene,
     CornellBoxScene,
+    CornellDownlightsScene,
     CornellSmokeScene,
+    CornellSubsurfaceScene,
+    GlowingScreenScene,
     FinalScene,
 }
 
-pub fn scene_selector(world: WorldEnum, image_width: u32, image_height: u32) -> (Color, Camera, HittableList) {
+pub fn scene_selector(world: WorldEnum, image_width: u32, image_height: u32) -> (Background, Arc<dyn Camera + Send + Sync>, HittableList, LightList) {
     match world {
         WorldEnum::OneWeekendScene => one_weekend_scene(image_width, image_height),
+        WorldEnum::PhotographScene => one_weekend_photograph(image_width, image_height),
+        WorldEnum::LensSystemScene => one_weekend_lens_system(image_width, image_height),
+        WorldEnum::TiltShiftScene => one_weekend_tilt_shift(image_width, image_height),
+        WorldEnum::PanoramaScene => one_weekend_panorama(image_width, image_height),
+        WorldEnum::StereoPanoramaScene => one_weekend_stereo_panorama(image_width, image_height),
+        WorldEnum::FisheyeScene => one_weekend_fisheye(image_width, image_height),
+        WorldEnum::ManyLightsScene => many_lights(image_width, image_height),
+        WorldEnum::BokehScene => bokeh(image_width, image_height),
         WorldEnum::MovableWeekendScene => movable_one_weekend(image_width, image_height),
         WorldEnum::TwoTexturedSpheresScene => two_textured_spheres_scene(image_width, image_height),
         WorldEnum::TwoPerlinSpheresScene => two_perlin_spheres(image_width, image_height),
+        WorldEnum::BumpMappedScene => bump_mapped_spheres(image_width, image_height),
+        WorldEnum::AlphaMaskedScene => alpha_masked_quad(image_width, image_height),
+        WorldEnum::BrushedMetalScene => brushed_metal(image_width, image_height),
+        WorldEnum::OrthographicScene => orthographic_product_shot(image_width, image_height),
+        WorldEnum::ThinFilmScene => thin_films(image_width, image_height),
+        WorldEnum::EnvironmentScene => environment_lit(image_width, image_height),
+        WorldEnum::DeltaLightsScene => delta_lights(image_width, image_height),
+        WorldEnum::LightLinkingScene => light_linking(image_width, image_height),
         WorldEnum::EarthScene => earth(image_width, image_height),
         WorldEnum::DiffuseLightScene => diffuse_light(image_width, image_height),
+        WorldEnum::DispersionScene => dispersion(image_width, image_height),
         WorldEnum::CornellBoxScene => cornell_box(image_width, image_height),
+        WorldEnum::CornellDownlightsScene => cornell_downlights(image_width, image_height),
         WorldEnum::CornellSmokeScene => cornell_smoke(image_width, image_height),
+        WorldEnum::CornellSubsurfaceScene => cornell_subsurface(image_width, image_height),
+        WorldEnum::GlowingScreenScene => glowing_screen(image_width, image_height),
         WorldEnum::FinalScene => final_scene(image_width, image_height),
     }
 }
\ No newline at end of file